@@ -0,0 +1,60 @@
+// Constant-expression folding for module-level assignments (`a = 5 * 3 + 2`, `MASK = 1 << 8`,
+// `T = 3.0 / 2`): thin aggregation on top of `expr_ast`'s shunting-yard parser and its
+// `try_eval_checked`, the same "aggregate a Vec from File::get_global_variables()" shape
+// `dataflow.rs` already uses for per-variable usage. The checked-arithmetic evaluation itself
+// lives in `expr_ast.rs` alongside the `Expr` tree it walks, rather than here, since it's
+// general-purpose evaluation of an already-parsed expression, not file-level aggregation.
+
+use crate::expr_ast::{parse_expression, try_eval_checked, CheckedEvalOutcome, ConstValue};
+use crate::{File, WarningType};
+
+// One module-level name whose value is known at fold time.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ConstantBinding {
+    pub name: String,
+    pub value: ConstValue,
+    pub line: usize,
+}
+
+// Every module-level assignment in `file` whose right-hand side parses and checked-evaluates to a
+// concrete value -- the "table of known compile-time constant values" callers can inspect.
+pub fn fold_file_constants(file: &File) -> Vec<ConstantBinding> {
+    let mut bindings: Vec<ConstantBinding> = Vec::new();
+    for var in file.get_global_variables() {
+        if var.get_name().contains('.') || var.get_name().contains('[') {
+            // An attribute (`obj.field = ...`) or subscript (`data[0] = ...`) target isn't a fresh
+            // module-level constant -- it's a mutation of an existing name.
+            continue;
+        }
+        let expr = match parse_expression(var.get_value()) {
+            Some(expr) => expr,
+            None => continue,
+        };
+        if let CheckedEvalOutcome::Value(value) = try_eval_checked(&expr) {
+            bindings.push(ConstantBinding { name: var.get_name().clone(), value: value, line: var.get_source().get_number() });
+        }
+    }
+    return bindings;
+}
+
+// Module-level assignments whose right-hand side is fully literal but can't actually be
+// evaluated (overflow, division/modulo by zero, ...), surfaced as raw `(WarningType, line,
+// message)` tuples for `Diagnostics::collect` to turn into `RuleDiagnostic`s the same way every
+// other `detect_*` rule in `lib.rs` does.
+pub fn detect_constant_arithmetic_errors(file: &File) -> Vec<(WarningType, usize, String)> {
+    let mut findings: Vec<(WarningType, usize, String)> = Vec::new();
+    for var in file.get_global_variables() {
+        let expr = match parse_expression(var.get_value()) {
+            Some(expr) => expr,
+            None => continue,
+        };
+        if let CheckedEvalOutcome::Error(message) = try_eval_checked(&expr) {
+            findings.push((
+                WarningType::ConstantArithmeticError,
+                var.get_source().get_number(),
+                format!("Constant expression for '{}' could not be evaluated: {}.", var.get_name(), message),
+            ));
+        }
+    }
+    return findings;
+}
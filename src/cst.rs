@@ -0,0 +1,258 @@
+// A first slice of a lossless concrete syntax tree for Python expression snippets, meant to
+// eventually replace the duplicated in_single_quotations/bracket_depth/square_bracket_depth/
+// curly_bracket_depth bookkeeping that is currently hand-rolled separately in
+// is_enclosed_in_brackets, is_function_call, is_array_access,
+// contains_arithmetic_symbols_not_enclosed, split_by_char and the big splitter match in
+// handle_assignment_expression_core.
+//
+// This only covers lexing plus bracket nesting so far: `lex` produces spanned, lossless tokens
+// (concatenating every token's text reconstructs the input exactly, including whitespace and
+// comments), and `parse` groups those tokens into a tree of Parens/Brackets/Braces nodes, with
+// `complete: false` marking a group whose closing bracket was never found (an unterminated-bracket
+// error, as a tree property instead of a silently-wrong depth counter). A single query,
+// `is_enclosed_in_brackets_cst`, is reimplemented on top of the tree as a proof of concept. Widening
+// this module to cover operators/identifiers/numbers as distinct node kinds and migrating the rest
+// of the predicates above onto it is future work; the existing functions are left untouched so the
+// tests pinning their current behavior keep passing.
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum TokenKind {
+    Whitespace,
+    StringLiteral,
+    UnterminatedStringLiteral,
+    LParen,
+    RParen,
+    LBracket,
+    RBracket,
+    LBrace,
+    RBrace,
+    Other,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Token {
+    pub kind: TokenKind,
+    pub text: String,
+    pub start: usize,
+    pub end: usize,
+}
+
+// Lexes `text` into a lossless, fully-covering stream of tokens. String literals (single- or
+// double-quoted, backslash-escape aware, but not prefix-aware yet) are consumed as one token each;
+// everything else that isn't whitespace or a bracket character is grouped into runs of `Other`.
+pub fn lex(text: &str) -> Vec<Token> {
+    let chars: Vec<char> = text.chars().collect();
+    let mut tokens: Vec<Token> = Vec::new();
+    let mut index: usize = 0;
+    let mut other_start: Option<usize> = None;
+
+    fn flush_other(tokens: &mut Vec<Token>, chars: &Vec<char>, other_start: &mut Option<usize>, end: usize) {
+        if let Some(start) = other_start.take() {
+            if end > start {
+                tokens.push(Token {
+                    kind: TokenKind::Other,
+                    text: chars[start..end].iter().collect(),
+                    start: start,
+                    end: end,
+                });
+            }
+        }
+    }
+
+    while index < chars.len() {
+        let c: char = chars[index];
+        match c {
+            ' ' | '\t' | '\n' | '\r' => {
+                flush_other(&mut tokens, &chars, &mut other_start, index);
+                let start: usize = index;
+                while index < chars.len() && matches!(chars[index], ' ' | '\t' | '\n' | '\r') {
+                    index += 1;
+                }
+                tokens.push(Token { kind: TokenKind::Whitespace, text: chars[start..index].iter().collect(), start: start, end: index });
+            },
+            '(' | ')' | '[' | ']' | '{' | '}' => {
+                flush_other(&mut tokens, &chars, &mut other_start, index);
+                let kind: TokenKind = match c {
+                    '(' => TokenKind::LParen,
+                    ')' => TokenKind::RParen,
+                    '[' => TokenKind::LBracket,
+                    ']' => TokenKind::RBracket,
+                    '{' => TokenKind::LBrace,
+                    _ => TokenKind::RBrace,
+                };
+                tokens.push(Token { kind: kind, text: c.to_string(), start: index, end: index + 1 });
+                index += 1;
+            },
+            '\'' | '\"' => {
+                flush_other(&mut tokens, &chars, &mut other_start, index);
+                let quote: char = c;
+                let start: usize = index;
+                index += 1;
+                let mut terminated: bool = false;
+                while index < chars.len() {
+                    if chars[index] == '\\' && index + 1 < chars.len() {
+                        index += 2;
+                        continue;
+                    }
+                    if chars[index] == quote {
+                        index += 1;
+                        terminated = true;
+                        break;
+                    }
+                    index += 1;
+                }
+                let kind: TokenKind = if terminated { TokenKind::StringLiteral } else { TokenKind::UnterminatedStringLiteral };
+                tokens.push(Token { kind: kind, text: chars[start..index].iter().collect(), start: start, end: index });
+            },
+            _ => {
+                if other_start.is_none() {
+                    other_start = Some(index);
+                }
+                index += 1;
+            }
+        }
+    }
+    flush_other(&mut tokens, &chars, &mut other_start, index);
+
+    return tokens;
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum NodeKind {
+    Root,
+    Parens,
+    Brackets,
+    Braces,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum SyntaxElement {
+    Token(Token),
+    Node(SyntaxNode),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct SyntaxNode {
+    pub kind: NodeKind,
+    pub children: Vec<SyntaxElement>,
+    // False when this node's closing bracket was never found before the end of input (or, for
+    // Root, always true): an unterminated-bracket error surfaced as a tree property.
+    pub complete: bool,
+}
+
+fn opening_for(kind: &NodeKind) -> Option<TokenKind> {
+    return match kind {
+        NodeKind::Parens => Some(TokenKind::LParen),
+        NodeKind::Brackets => Some(TokenKind::LBracket),
+        NodeKind::Braces => Some(TokenKind::LBrace),
+        NodeKind::Root => None,
+    };
+}
+
+// Groups a lexed token stream into a tree of bracket-nesting nodes. Unmatched closing brackets are
+// attached as plain tokens of the nearest enclosing node (they don't close anything, since nothing
+// matching is open); any node still open when the tokens run out is marked `complete: false`.
+pub fn parse(tokens: Vec<Token>) -> SyntaxNode {
+    let mut stack: Vec<SyntaxNode> = vec![SyntaxNode { kind: NodeKind::Root, children: Vec::new(), complete: true }];
+
+    for token in tokens {
+        let open_kind: Option<NodeKind> = match token.kind {
+            TokenKind::LParen => Some(NodeKind::Parens),
+            TokenKind::LBracket => Some(NodeKind::Brackets),
+            TokenKind::LBrace => Some(NodeKind::Braces),
+            _ => None,
+        };
+        if let Some(kind) = open_kind {
+            stack.push(SyntaxNode { kind: kind, children: vec![SyntaxElement::Token(token)], complete: false });
+            continue;
+        }
+
+        let closes_current: bool = match (&token.kind, stack.last().map(|n| &n.kind)) {
+            (TokenKind::RParen, Some(NodeKind::Parens)) => true,
+            (TokenKind::RBracket, Some(NodeKind::Brackets)) => true,
+            (TokenKind::RBrace, Some(NodeKind::Braces)) => true,
+            _ => false,
+        };
+        if closes_current {
+            let mut finished: SyntaxNode = stack.pop().unwrap();
+            finished.children.push(SyntaxElement::Token(token));
+            finished.complete = true;
+            stack.last_mut().unwrap().children.push(SyntaxElement::Node(finished));
+            continue;
+        }
+
+        stack.last_mut().unwrap().children.push(SyntaxElement::Token(token));
+    }
+
+    while stack.len() > 1 {
+        let finished: SyntaxNode = stack.pop().unwrap();
+        stack.last_mut().unwrap().children.push(SyntaxElement::Node(finished));
+    }
+    return stack.pop().unwrap();
+}
+
+// Recreates the original text from a syntax tree by concatenating every token's text in order;
+// used to confirm a tree is lossless (round-trips back to its input).
+pub fn to_text(node: &SyntaxNode) -> String {
+    let mut out: String = String::new();
+    for child in &node.children {
+        match child {
+            SyntaxElement::Token(token) => out.push_str(&token.text),
+            SyntaxElement::Node(inner) => out.push_str(&to_text(inner)),
+        }
+    }
+    return out;
+}
+
+// Proof-of-concept query over the tree: true if the whole snippet is one outermost, complete
+// parenthesis/bracket/brace pair wrapping everything else (the same question
+// `is_enclosed_in_brackets` in lib.rs answers via an independent depth-counter scan).
+pub fn is_enclosed_in_brackets_cst(text: &str) -> bool {
+    let root: SyntaxNode = parse(lex(text));
+    let meaningful: Vec<&SyntaxElement> = root.children.iter()
+        .filter(|child| !matches!(child, SyntaxElement::Token(Token { kind: TokenKind::Whitespace, .. })))
+        .collect();
+    if meaningful.len() != 1 {
+        return false;
+    }
+    return match meaningful[0] {
+        SyntaxElement::Node(inner) => inner.complete && !matches!(inner.kind, NodeKind::Root) && opening_for(&inner.kind).is_some(),
+        SyntaxElement::Token(_) => false,
+    };
+}
+
+// A `Token` stamped with the source row it came from, the missing piece for turning `lex`'s
+// single-line char offsets into the `(start_row, start_col, end_col)` a multi-line tree walk
+// needs. Row numbers match `Line::get_number` (1-based).
+#[derive(Clone, Debug, PartialEq)]
+pub struct PositionedToken {
+    pub token: Token,
+    pub row: usize,
+}
+
+impl PositionedToken {
+
+    pub fn start_col(&self) -> usize {
+        return self.token.start;
+    }
+
+    pub fn end_col(&self) -> usize {
+        return self.token.end;
+    }
+
+}
+
+// Lexes every line of `source` independently (this crate's existing grammar is line-oriented --
+// a logical line never continues past an unbracketed newline once `StructureTracker` has joined
+// any bracket/backslash continuations into one `Line` already) and stamps each resulting token
+// with its row, giving the full `(row, start_col, end_col)` triple per token across a real source
+// file instead of just one line's worth of char offsets.
+pub fn lex_lines(source: &Vec<crate::Line>) -> Vec<PositionedToken> {
+    let mut positioned: Vec<PositionedToken> = Vec::new();
+    for line in source {
+        for token in lex(line.get_text()) {
+            positioned.push(PositionedToken { token: token, row: line.get_number() });
+        }
+    }
+    return positioned;
+}
@@ -0,0 +1,109 @@
+// Per-file variable dataflow: which module-level names are read before they are ever assigned, a
+// strong signal for a typo or a missing import. Built on the same "new" (names being defined) /
+// "check" (names being read) split `get_variables_from_assignment` already computes for
+// `File::scan`'s undefined-variable pass, just aggregated across the whole file instead of used to
+// fire one warning per occurrence.
+//
+// Names are seeded as already defined the same way `File::scan` seeds its scope before walking
+// global variables one at a time: imports and the file's own function/class names are all hoisted
+// in Python (callable/referenceable regardless of where in the module they're defined), so only the
+// global variable assignments themselves are walked in source order to tell a read from a
+// definition.
+//
+// Serializes to the same delimited-table format `warnings.rs` uses (`delimited.rs`).
+
+use std::collections::{HashMap, HashSet};
+
+use crate::delimited::{DelimitedFormat, delimited_record};
+use crate::{File, get_variables_from_assignment};
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct VariableUsage {
+    pub name: String,
+    pub first_defined_line: Option<usize>,
+    pub read_lines: Vec<usize>,
+    pub used_before_def: bool,
+}
+
+impl VariableUsage {
+    pub fn read_count(&self) -> usize {
+        return self.read_lines.len();
+    }
+}
+
+fn record_index(records: &mut Vec<VariableUsage>, index_by_name: &mut HashMap<String, usize>, name: &str) -> usize {
+    return *index_by_name.entry(name.to_string()).or_insert_with(|| {
+        records.push(VariableUsage { name: name.to_string(), first_defined_line: None, read_lines: Vec::new(), used_before_def: false });
+        records.len() - 1
+    });
+}
+
+// Aggregates per-variable read/define line numbers across `file`'s module-level assignments.
+pub fn analyze_dataflow(file: &File) -> Vec<VariableUsage> {
+    let mut defined: HashSet<String> = HashSet::new();
+    defined.insert("False".to_string());
+    defined.insert("True".to_string());
+    for import in file.get_imports() {
+        defined.insert(import.clone());
+    }
+    for function in file.get_functions() {
+        defined.insert(function.get_name().clone());
+    }
+    for class in file.get_classes() {
+        defined.insert(class.get_name().clone());
+    }
+
+    let mut records: Vec<VariableUsage> = Vec::new();
+    let mut index_by_name: HashMap<String, usize> = HashMap::new();
+
+    for var in file.get_global_variables() {
+        if var.get_name().contains('.') {
+            // An attribute/subscript target (`a.b = ...`) isn't a fresh module-level name.
+            continue;
+        }
+        let line: usize = var.get_source().get_number();
+        let variables: HashMap<String, Vec<String>> = get_variables_from_assignment(var.clone());
+
+        for name in variables.get("check").cloned().unwrap_or_default() {
+            let already_defined: bool = defined.contains(&name);
+            let index: usize = record_index(&mut records, &mut index_by_name, &name);
+            records[index].read_lines.push(line);
+            if !already_defined {
+                records[index].used_before_def = true;
+            }
+        }
+        for name in variables.get("new").cloned().unwrap_or_default() {
+            let index: usize = record_index(&mut records, &mut index_by_name, &name);
+            if records[index].first_defined_line.is_none() {
+                records[index].first_defined_line = Some(line);
+            }
+            defined.insert(name);
+        }
+    }
+
+    return records;
+}
+
+const DATAFLOW_HEADER: [&str; 5] = ["name", "first_defined_line", "read_count", "read_lines", "used_before_def"];
+
+// Serializes `records` as delimited records per `format`: an optional header row, then one row per
+// variable. `read_lines` is rendered as its own semicolon-joined sub-field (so it survives a single
+// CSV/TSV column) and is itself still subject to the outer quoting rule if it contains the record's
+// delimiter.
+pub fn dataflow_to_delimited(records: &[VariableUsage], format: &DelimitedFormat) -> String {
+    let mut result: String = String::new();
+    if format.include_header {
+        result.push_str(&delimited_record(&DATAFLOW_HEADER.map(String::from), format));
+    }
+    for record in records {
+        let read_lines: String = record.read_lines.iter().map(|line| line.to_string()).collect::<Vec<String>>().join(";");
+        result.push_str(&delimited_record(&[
+            record.name.clone(),
+            record.first_defined_line.map(|line| line.to_string()).unwrap_or_default(),
+            record.read_count().to_string(),
+            read_lines,
+            record.used_before_def.to_string(),
+        ], format));
+    }
+    return result;
+}
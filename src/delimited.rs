@@ -0,0 +1,75 @@
+// Shared plumbing for every "table of records as delimited text" export in this crate
+// (`warnings.rs`'s scan findings, `dataflow.rs`'s per-variable usage table, `csv_export.rs`'s
+// parsed-tree dump, ...): a configurable field delimiter, record terminator, and quote style,
+// modeled loosely on the `csv` crate's own `WriterBuilder` -- so CSV and TSV (or any other
+// single-character delimiter), `\n` or `\r\n` line endings, and quote-only-when-needed vs.
+// always-quoted fields all share one implementation instead of each export reinventing it.
+//
+// Quoting itself keeps the convention `csv_export.rs` established first: a field containing the
+// delimiter, a double quote, or a newline is wrapped in double quotes, with interior double quotes
+// doubled.
+
+// The line ending a delimited record is written with -- `\n` for CSV-on-Unix conventions, `\r\n`
+// for tools (older spreadsheet importers, some Windows pipelines) that expect it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RecordTerminator {
+    Lf,
+    CrLf,
+}
+
+impl RecordTerminator {
+    pub fn as_str(&self) -> &'static str {
+        return match self {
+            RecordTerminator::Lf => "\n",
+            RecordTerminator::CrLf => "\r\n",
+        };
+    }
+}
+
+// Minimal quotes only the fields that need it (contain the delimiter, a quote, or a newline);
+// Always quotes every field regardless of content, the way some downstream tools expect.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum QuoteStyle {
+    Minimal,
+    Always,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct DelimitedFormat {
+    pub delimiter: char,
+    pub terminator: RecordTerminator,
+    pub include_header: bool,
+    pub quote_style: QuoteStyle,
+}
+
+impl DelimitedFormat {
+    // Comma-delimited, `\n`-terminated, quote-only-when-needed, with a header row -- the common
+    // case for spreadsheets.
+    pub fn csv() -> Self {
+        return DelimitedFormat { delimiter: ',', terminator: RecordTerminator::Lf, include_header: true, quote_style: QuoteStyle::Minimal };
+    }
+
+    // Tab-delimited, `\n`-terminated, quote-only-when-needed, with a header row -- for tools that
+    // choke on commas inside an unquoted field.
+    pub fn tsv() -> Self {
+        return DelimitedFormat { delimiter: '\t', terminator: RecordTerminator::Lf, include_header: true, quote_style: QuoteStyle::Minimal };
+    }
+}
+
+fn needs_quoting(field: &str, delimiter: char) -> bool {
+    return field.contains(delimiter) || field.contains('"') || field.contains('\n') || field.contains('\r');
+}
+
+pub fn quote_delimited_field(field: &str, delimiter: char, quote_style: QuoteStyle) -> String {
+    if quote_style == QuoteStyle::Always || needs_quoting(field, delimiter) {
+        return format!("\"{}\"", field.replace('"', "\"\""));
+    }
+    return field.to_string();
+}
+
+// Joins `fields` with `format.delimiter`, quoting each per `quote_delimited_field`/`format.quote_style`,
+// and terminates the row per `format.terminator`.
+pub fn delimited_record(fields: &[String], format: &DelimitedFormat) -> String {
+    let quoted: Vec<String> = fields.iter().map(|field| quote_delimited_field(field, format.delimiter, format.quote_style)).collect();
+    return quoted.join(&format.delimiter.to_string()) + format.terminator.as_str();
+}
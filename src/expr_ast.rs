@@ -0,0 +1,714 @@
+// A shunting-yard expression parser, giving a typed AST for a Python expression snippet instead
+// of the hand-rolled recursive string-splitting `handle_assignment_expression_core` in lib.rs
+// does (which recognizes calls/subscripts/attribute access but hand-splits on `+ - % ^ & | * /`
+// with no operator precedence and no comparison/unary handling). This lives alongside that
+// pipeline rather than replacing it, the same way peg_grammar.rs sits alongside the regex-based
+// statement classification: scan()/the dead-store pass/extraction suggestions all walk the
+// string-split result and their pinned warning text moves with it, so swapping the expression
+// engine out from under them is a separate, riskier change. Callers that want precedence-correct
+// parsing, or just a typed tree instead of a flat name list, can call `parse_expression` directly.
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Expr {
+    Name(String),
+    Number(String),
+    Str(String),
+    UnaryOp { op: String, operand: Box<Expr> },
+    BinaryOp { op: String, left: Box<Expr>, right: Box<Expr> },
+    Call { callee: Box<Expr>, arguments: Vec<Expr> },
+    Subscript { target: Box<Expr>, index: Box<Expr> },
+    Attribute { target: Box<Expr>, name: String },
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Name(String),
+    Number(String),
+    Str(String),
+    Op(String),
+    LParen,
+    RParen,
+    LBracket,
+    RBracket,
+    Dot,
+    Comma,
+}
+
+fn tokenize(text: &str) -> Vec<Token> {
+    let mut tokens: Vec<Token> = Vec::new();
+    let chars: Vec<char> = text.chars().collect();
+    let mut index: usize = 0;
+    while index < chars.len() {
+        let c: char = chars[index];
+        if c.is_whitespace() {
+            index += 1;
+            continue;
+        }
+        if c == '\'' || c == '\"' {
+            let quote: char = c;
+            let mut literal: String = String::new();
+            literal.push(c);
+            index += 1;
+            while index < chars.len() {
+                let d: char = chars[index];
+                literal.push(d);
+                index += 1;
+                if d == '\\' && index < chars.len() {
+                    literal.push(chars[index]);
+                    index += 1;
+                    continue;
+                }
+                if d == quote {
+                    break;
+                }
+            }
+            tokens.push(Token::Str(literal));
+            continue;
+        }
+        if c.is_ascii_digit() {
+            let mut number: String = String::new();
+            while index < chars.len() && (chars[index].is_ascii_digit() || chars[index] == '.') {
+                number.push(chars[index]);
+                index += 1;
+            }
+            tokens.push(Token::Number(number));
+            continue;
+        }
+        if c.is_alphabetic() || c == '_' {
+            let mut name: String = String::new();
+            while index < chars.len() && (chars[index].is_alphanumeric() || chars[index] == '_') {
+                name.push(chars[index]);
+                index += 1;
+            }
+            tokens.push(Token::Name(name));
+            continue;
+        }
+        match c {
+            '(' => { tokens.push(Token::LParen); index += 1; },
+            ')' => { tokens.push(Token::RParen); index += 1; },
+            '[' => { tokens.push(Token::LBracket); index += 1; },
+            ']' => { tokens.push(Token::RBracket); index += 1; },
+            '.' => { tokens.push(Token::Dot); index += 1; },
+            ',' => { tokens.push(Token::Comma); index += 1; },
+            '*' | '/' | '<' | '>' | '=' | '!' => {
+                // Two-character operators: ** // << >> <= >= == !=
+                if index + 1 < chars.len() {
+                    let two: String = format!("{}{}", c, chars[index + 1]);
+                    if ["**", "//", "<<", ">>", "<=", ">=", "==", "!="].contains(&two.as_str()) {
+                        tokens.push(Token::Op(two));
+                        index += 2;
+                        continue;
+                    }
+                }
+                tokens.push(Token::Op(c.to_string()));
+                index += 1;
+            },
+            '+' | '-' | '%' | '^' | '&' | '|' | '~' | '@' => {
+                tokens.push(Token::Op(c.to_string()));
+                index += 1;
+            },
+            _ => {
+                // Unrecognized character (e.g. ':' in a slice, or a string prefix we don't model):
+                // skip it rather than failing the whole parse.
+                index += 1;
+            }
+        }
+    }
+    return tokens;
+}
+
+fn precedence(op: &str) -> u8 {
+    match op {
+        "or" => 1,
+        "and" => 2,
+        "<" | "<=" | ">" | ">=" | "==" | "!=" | "in" | "not in" | "is" | "is not" => 4,
+        "|" => 5,
+        "^" => 6,
+        "&" => 7,
+        "<<" | ">>" => 8,
+        "+" | "-" => 9,
+        "*" | "/" | "//" | "%" | "@" => 10,
+        "**" => 12,
+        _ => 0,
+    }
+}
+
+fn is_right_associative(op: &str) -> bool {
+    return op == "**";
+}
+
+// Runs the shunting-yard algorithm over `tokens`, then folds the resulting output queue/operator
+// stack into an Expr tree. `(`/`[`/`.` right after an operand are handled as postfix call/
+// subscript/attribute tokens rather than grouping, mirroring how a real Python parser
+// distinguishes `f(x)` from `(x)`.
+pub fn parse_expression(text: &str) -> Option<Expr> {
+    let tokens: Vec<Token> = tokenize(text);
+    if tokens.is_empty() {
+        return None;
+    }
+
+    let mut output: Vec<Expr> = Vec::new();
+    let mut operators: Vec<String> = Vec::new();
+    // Tracks, for every '(' pushed as a grouping paren (not a call), how many comma-separated
+    // arguments have been seen so far, so Call nodes can be assembled when the matching ')' pops.
+    let mut call_arg_counts: Vec<usize> = Vec::new();
+    let mut is_call_paren: Vec<bool> = Vec::new();
+    let mut expect_operand: bool = true;
+
+    fn apply_operator(output: &mut Vec<Expr>, op: String) {
+        if op == "u-" || op == "u+" || op == "u~" || op == "not" {
+            if let Some(operand) = output.pop() {
+                output.push(Expr::UnaryOp { op: op.trim_start_matches('u').to_string(), operand: Box::new(operand) });
+            }
+            return;
+        }
+        if let (Some(right), Some(left)) = (output.pop(), output.pop()) {
+            output.push(Expr::BinaryOp { op: op, left: Box::new(left), right: Box::new(right) });
+        }
+    }
+
+    // Pops operators of higher-or-equal precedence off `operators` (applying each to `output`)
+    // before pushing `op`, i.e. the body of the shunting-yard loop shared by symbolic operators
+    // (`Token::Op`) and the keyword operators below (`in`, `is`, `is not`, `not in`).
+    fn push_operator_with_precedence(operators: &mut Vec<String>, output: &mut Vec<Expr>, op: String) {
+        let (prec, right_assoc): (u8, bool) = if op.starts_with('u') {
+            (11, true)
+        } else {
+            (precedence(&op), is_right_associative(&op))
+        };
+        while let Some(top) = operators.last() {
+            if top == "(" || top == "[" {
+                break;
+            }
+            let top_prec: u8 = if top.starts_with('u') { 11 } else { precedence(top) };
+            if top_prec > prec || (top_prec == prec && !right_assoc) {
+                let popped: String = operators.pop().unwrap();
+                apply_operator(output, popped);
+            } else {
+                break;
+            }
+        }
+        operators.push(op);
+    }
+
+    let mut index: usize = 0;
+    while index < tokens.len() {
+        let token: Token = tokens[index].clone();
+        match token {
+            Token::Number(n) => {
+                output.push(Expr::Number(n));
+                expect_operand = false;
+            },
+            Token::Str(s) => {
+                output.push(Expr::Str(s));
+                expect_operand = false;
+            },
+            Token::Name(n) => {
+                fn next_is_name(tokens: &Vec<Token>, index: usize, word: &str) -> bool {
+                    return matches!(tokens.get(index + 1), Some(Token::Name(next)) if next == word);
+                }
+                if n == "not" && expect_operand {
+                    operators.push("not".to_string());
+                } else if n == "not" && !expect_operand && next_is_name(&tokens, index, "in") {
+                    index += 1;
+                    push_operator_with_precedence(&mut operators, &mut output, "not in".to_string());
+                    expect_operand = true;
+                } else if n == "is" && !expect_operand {
+                    let op: String = if next_is_name(&tokens, index, "not") {
+                        index += 1;
+                        "is not".to_string()
+                    } else {
+                        "is".to_string()
+                    };
+                    push_operator_with_precedence(&mut operators, &mut output, op);
+                    expect_operand = true;
+                } else if n == "in" && !expect_operand {
+                    push_operator_with_precedence(&mut operators, &mut output, "in".to_string());
+                    expect_operand = true;
+                } else {
+                    output.push(Expr::Name(n));
+                    expect_operand = false;
+                }
+            },
+            Token::Op(op) => {
+                let op: String = if expect_operand {
+                    match op.as_str() {
+                        "-" => "u-".to_string(),
+                        "+" => "u+".to_string(),
+                        "~" => "u~".to_string(),
+                        _ => op,
+                    }
+                } else {
+                    op
+                };
+                push_operator_with_precedence(&mut operators, &mut output, op);
+                expect_operand = true;
+            },
+            Token::LParen => {
+                if !expect_operand {
+                    // Call: the operand already on the output queue is the callee.
+                    operators.push("(".to_string());
+                    is_call_paren.push(true);
+                    call_arg_counts.push(if matches!(tokens.get(index + 1), Some(Token::RParen)) { 0 } else { 1 });
+                } else {
+                    operators.push("(".to_string());
+                    is_call_paren.push(false);
+                    call_arg_counts.push(0);
+                }
+                expect_operand = true;
+            },
+            Token::RParen => {
+                while let Some(top) = operators.last() {
+                    if top == "(" {
+                        break;
+                    }
+                    let popped: String = operators.pop().unwrap();
+                    apply_operator(&mut output, popped);
+                }
+                operators.pop();
+                let was_call: bool = is_call_paren.pop().unwrap_or(false);
+                let arg_count: usize = call_arg_counts.pop().unwrap_or(0);
+                if was_call {
+                    let mut arguments: Vec<Expr> = Vec::new();
+                    for _ in 0..arg_count {
+                        if let Some(arg) = output.pop() {
+                            arguments.push(arg);
+                        }
+                    }
+                    arguments.reverse();
+                    if let Some(callee) = output.pop() {
+                        output.push(Expr::Call { callee: Box::new(callee), arguments: arguments });
+                    }
+                }
+                expect_operand = false;
+            },
+            Token::LBracket => {
+                operators.push("[".to_string());
+                expect_operand = true;
+            },
+            Token::RBracket => {
+                while let Some(top) = operators.last() {
+                    if top == "[" {
+                        break;
+                    }
+                    let popped: String = operators.pop().unwrap();
+                    apply_operator(&mut output, popped);
+                }
+                operators.pop();
+                if let (Some(index_expr), Some(target)) = (output.pop(), output.pop()) {
+                    output.push(Expr::Subscript { target: Box::new(target), index: Box::new(index_expr) });
+                }
+                expect_operand = false;
+            },
+            Token::Dot => {
+                // Attribute access: the next token must be a name, consumed here directly.
+                index += 1;
+                if let Some(Token::Name(attr)) = tokens.get(index) {
+                    if let Some(target) = output.pop() {
+                        output.push(Expr::Attribute { target: Box::new(target), name: attr.clone() });
+                    }
+                }
+                expect_operand = false;
+            },
+            Token::Comma => {
+                while let Some(top) = operators.last() {
+                    if top == "(" || top == "[" {
+                        break;
+                    }
+                    let popped: String = operators.pop().unwrap();
+                    apply_operator(&mut output, popped);
+                }
+                if let Some(count) = call_arg_counts.last_mut() {
+                    *count += 1;
+                }
+                expect_operand = true;
+            },
+        }
+        index += 1;
+    }
+
+    while let Some(op) = operators.pop() {
+        if op == "(" || op == "[" {
+            continue;
+        }
+        apply_operator(&mut output, op);
+    }
+
+    return output.pop();
+}
+
+// Walks an Expr tree and collects every Name it references (skipping attribute names, which
+// belong to whatever object they're accessed on rather than being standalone references).
+pub fn collect_names(expr: &Expr, names: &mut Vec<String>) {
+    match expr {
+        Expr::Name(n) => {
+            if !names.contains(n) {
+                names.push(n.clone());
+            }
+        },
+        Expr::Number(_) | Expr::Str(_) => (),
+        Expr::UnaryOp { operand, .. } => collect_names(operand, names),
+        Expr::BinaryOp { left, right, .. } => {
+            collect_names(left, names);
+            collect_names(right, names);
+        },
+        Expr::Call { callee, arguments } => {
+            collect_names(callee, names);
+            for argument in arguments {
+                collect_names(argument, names);
+            }
+        },
+        Expr::Subscript { target, index } => {
+            collect_names(target, names);
+            collect_names(index, names);
+        },
+        Expr::Attribute { target, .. } => collect_names(target, names),
+    }
+}
+
+// Evaluates `expr` to a concrete f64 if it is built entirely out of numeric literals and the
+// arithmetic operators below; returns None as soon as it hits a Name, Call, Str, or an operator it
+// doesn't know how to fold (including division by zero, which is left unfolded rather than
+// panicking or silently producing inf/NaN).
+pub fn try_eval_numeric(expr: &Expr) -> Option<f64> {
+    match expr {
+        Expr::Number(n) => n.parse::<f64>().ok(),
+        Expr::UnaryOp { op, operand } => {
+            let value: f64 = try_eval_numeric(operand)?;
+            match op.as_str() {
+                "-" => Some(-value),
+                "+" => Some(value),
+                _ => None,
+            }
+        },
+        Expr::BinaryOp { op, left, right } => {
+            let left_value: f64 = try_eval_numeric(left)?;
+            let right_value: f64 = try_eval_numeric(right)?;
+            match op.as_str() {
+                "+" => Some(left_value + right_value),
+                "-" => Some(left_value - right_value),
+                "*" => Some(left_value * right_value),
+                "/" => if right_value == 0.0 { None } else { Some(left_value / right_value) },
+                "//" => if right_value == 0.0 { None } else { Some((left_value / right_value).floor()) },
+                "%" => if right_value == 0.0 { None } else { Some(left_value % right_value) },
+                "**" => Some(left_value.powf(right_value)),
+                _ => None,
+            }
+        },
+        _ => None,
+    }
+}
+
+// Formats a folded numeric value the way a Python literal would look: integral results drop the
+// trailing ".0" so `1-1` folds to the index literal `0`, not `0.0`.
+fn format_folded_number(value: f64) -> String {
+    if value.fract() == 0.0 && value.is_finite() {
+        return format!("{}", value as i64);
+    }
+    return format!("{}", value);
+}
+
+// Recursively folds every foldable numeric subexpression of `expr` into a single Expr::Number,
+// leaving subexpressions that reference a Name, a Call, or a non-foldable operator untouched so
+// e.g. `arr[1-1]` folds to `arr[0]` while `arr[i-1]` is left as `arr[i-1]`.
+pub fn fold_constants(expr: &Expr) -> Expr {
+    if let Some(value) = try_eval_numeric(expr) {
+        return Expr::Number(format_folded_number(value));
+    }
+    match expr {
+        Expr::UnaryOp { op, operand } => Expr::UnaryOp { op: op.clone(), operand: Box::new(fold_constants(operand)) },
+        Expr::BinaryOp { op, left, right } => Expr::BinaryOp { op: op.clone(), left: Box::new(fold_constants(left)), right: Box::new(fold_constants(right)) },
+        Expr::Call { callee, arguments } => Expr::Call {
+            callee: Box::new(fold_constants(callee)),
+            arguments: arguments.iter().map(fold_constants).collect(),
+        },
+        Expr::Subscript { target, index } => Expr::Subscript { target: Box::new(fold_constants(target)), index: Box::new(fold_constants(index)) },
+        Expr::Attribute { target, name } => Expr::Attribute { target: Box::new(fold_constants(target)), name: name.clone() },
+        other => other.clone(),
+    }
+}
+
+// Convenience entry point combining parsing and constant folding, for callers that want e.g.
+// `arr[1-1]` to come back as a Subscript whose index is already Expr::Number("0") rather than
+// having to fold the tree themselves.
+pub fn parse_expression_folded(text: &str) -> Option<Expr> {
+    return parse_expression(text).map(|expr| fold_constants(&expr));
+}
+
+// A Python literal value folded out of a checked evaluation (see `try_eval_checked` below).
+// Promotion to `Float` happens whenever either operand of a binary operator is itself a `Float`;
+// `Bool` only ever survives as the *final* result of evaluating a bare `True`/`False` literal with
+// no operator applied to it at all, since Python's bool-is-an-int semantics mean any operator
+// (even `True + True`) already promotes to `Int`, matching `int(True)`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ConstValue {
+    Int(i64),
+    Float(f64),
+    Bool(bool),
+}
+
+impl ConstValue {
+    pub fn as_string(&self) -> String {
+        return match self {
+            ConstValue::Int(value) => value.to_string(),
+            ConstValue::Float(value) => value.to_string(),
+            ConstValue::Bool(value) => value.to_string(),
+        };
+    }
+
+    fn as_f64(&self) -> f64 {
+        return match self {
+            ConstValue::Int(value) => *value as f64,
+            ConstValue::Float(value) => *value,
+            ConstValue::Bool(value) => if *value { 1.0 } else { 0.0 },
+        };
+    }
+
+    // `None` for `Float`, since bitwise/shift operators and the checked-integer arithmetic path
+    // below are only defined over Python's int domain (which `bool` is a subtype of).
+    fn as_i64(&self) -> Option<i64> {
+        return match self {
+            ConstValue::Int(value) => Some(*value),
+            ConstValue::Bool(value) => Some(if *value { 1 } else { 0 }),
+            ConstValue::Float(_) => None,
+        };
+    }
+
+    fn is_float(&self) -> bool {
+        return matches!(self, ConstValue::Float(_));
+    }
+}
+
+// The result of attempting to checked-evaluate one `Expr` to a concrete value.
+#[derive(Clone, Debug, PartialEq)]
+pub enum CheckedEvalOutcome {
+    // The expression is fully literal and evaluated cleanly to this value.
+    Value(ConstValue),
+    // The expression references a Name (other than the `True`/`False` literals), a Call, a
+    // Subscript, an Attribute, or a Str -- not an error, just not evaluable at fold time.
+    NotConstant,
+    // The expression is fully literal but the arithmetic itself can't be carried out (overflow,
+    // division/modulo by zero, or a bitwise/shift operator applied to a float operand).
+    Error(String),
+}
+
+fn apply_checked_unary(op: &str, value: ConstValue) -> CheckedEvalOutcome {
+    match op {
+        "-" => match value {
+            ConstValue::Float(number) => CheckedEvalOutcome::Value(ConstValue::Float(-number)),
+            _ => match value.as_i64().unwrap().checked_neg() {
+                Some(result) => CheckedEvalOutcome::Value(ConstValue::Int(result)),
+                None => CheckedEvalOutcome::Error("arithmetic overflow negating a constant expression".to_string()),
+            },
+        },
+        "+" => match value {
+            ConstValue::Float(number) => CheckedEvalOutcome::Value(ConstValue::Float(number)),
+            _ => CheckedEvalOutcome::Value(ConstValue::Int(value.as_i64().unwrap())),
+        },
+        "~" => match value.as_i64() {
+            Some(number) => CheckedEvalOutcome::Value(ConstValue::Int(!number)),
+            None => CheckedEvalOutcome::Error("'~' requires an integer operand".to_string()),
+        },
+        // `not` is a boolean, not an arithmetic, operator -- out of scope for checked folding.
+        _ => CheckedEvalOutcome::NotConstant,
+    }
+}
+
+fn apply_checked_power(base: ConstValue, exponent: ConstValue) -> CheckedEvalOutcome {
+    // Only a non-negative integer exponent over integer operands stays in the checked-integer
+    // domain; anything else (a float operand, or a negative exponent) falls back to `f64::powf`,
+    // the same float promotion rule every other binary operator below follows.
+    if let (Some(base_int), Some(exponent_int)) = (base.as_i64(), exponent.as_i64()) {
+        if exponent_int >= 0 {
+            if let Ok(exponent_u32) = u32::try_from(exponent_int) {
+                return match base_int.checked_pow(exponent_u32) {
+                    Some(result) => CheckedEvalOutcome::Value(ConstValue::Int(result)),
+                    None => CheckedEvalOutcome::Error("arithmetic overflow evaluating a constant expression".to_string()),
+                };
+            }
+        }
+    }
+    if base.as_f64() == 0.0 && exponent.as_f64() < 0.0 {
+        return CheckedEvalOutcome::Error("division by zero".to_string());
+    }
+    return CheckedEvalOutcome::Value(ConstValue::Float(base.as_f64().powf(exponent.as_f64())));
+}
+
+// Python's `//` floors toward negative infinity (unlike Rust's truncating `/`), so e.g.
+// `-7 // 2 == -4`; this adjusts Rust's truncated quotient/remainder pair to match.
+fn floor_div_i64(left: i64, right: i64) -> Option<i64> {
+    if left == i64::MIN && right == -1 {
+        return None;
+    }
+    let quotient: i64 = left / right;
+    let remainder: i64 = left % right;
+    if remainder != 0 && (remainder < 0) != (right < 0) {
+        return Some(quotient - 1);
+    }
+    return Some(quotient);
+}
+
+// Python's `%` takes the sign of the divisor (unlike Rust's `%`, which takes the sign of the
+// dividend), so e.g. `-7 % 3 == 2` and `7 % -3 == -2`.
+fn python_mod_i64(left: i64, right: i64) -> i64 {
+    let remainder: i64 = left % right;
+    if remainder != 0 && (remainder < 0) != (right < 0) {
+        return remainder + right;
+    }
+    return remainder;
+}
+
+fn apply_checked_binary(op: &str, left: ConstValue, right: ConstValue) -> CheckedEvalOutcome {
+    match op {
+        "+" | "-" | "*" => {
+            if left.is_float() || right.is_float() {
+                let result: f64 = match op {
+                    "+" => left.as_f64() + right.as_f64(),
+                    "-" => left.as_f64() - right.as_f64(),
+                    _ => left.as_f64() * right.as_f64(),
+                };
+                return CheckedEvalOutcome::Value(ConstValue::Float(result));
+            }
+            let (a, b): (i64, i64) = (left.as_i64().unwrap(), right.as_i64().unwrap());
+            let result: Option<i64> = match op {
+                "+" => a.checked_add(b),
+                "-" => a.checked_sub(b),
+                _ => a.checked_mul(b),
+            };
+            return match result {
+                Some(value) => CheckedEvalOutcome::Value(ConstValue::Int(value)),
+                None => CheckedEvalOutcome::Error("arithmetic overflow evaluating a constant expression".to_string()),
+            };
+        },
+        "/" => {
+            // Python 3's '/' is always true division, even for two ints (`3 / 2 == 1.5`).
+            if right.as_f64() == 0.0 {
+                return CheckedEvalOutcome::Error("division by zero".to_string());
+            }
+            return CheckedEvalOutcome::Value(ConstValue::Float(left.as_f64() / right.as_f64()));
+        },
+        "//" => {
+            if !left.is_float() && !right.is_float() {
+                let (a, b): (i64, i64) = (left.as_i64().unwrap(), right.as_i64().unwrap());
+                if b == 0 {
+                    return CheckedEvalOutcome::Error("floor division by zero".to_string());
+                }
+                return match floor_div_i64(a, b) {
+                    Some(value) => CheckedEvalOutcome::Value(ConstValue::Int(value)),
+                    None => CheckedEvalOutcome::Error("arithmetic overflow evaluating a constant expression".to_string()),
+                };
+            }
+            if right.as_f64() == 0.0 {
+                return CheckedEvalOutcome::Error("floor division by zero".to_string());
+            }
+            return CheckedEvalOutcome::Value(ConstValue::Float((left.as_f64() / right.as_f64()).floor()));
+        },
+        "%" => {
+            if !left.is_float() && !right.is_float() {
+                let (a, b): (i64, i64) = (left.as_i64().unwrap(), right.as_i64().unwrap());
+                if b == 0 {
+                    return CheckedEvalOutcome::Error("modulo by zero".to_string());
+                }
+                // `i64::MIN % -1` panics in Rust the same way `i64::MIN / -1` does, even though
+                // the mathematical result (0) doesn't overflow -- guarded the same way
+                // `floor_div_i64` guards its own `i64::MIN`/`-1` case.
+                if a == i64::MIN && b == -1 {
+                    return CheckedEvalOutcome::Value(ConstValue::Int(0));
+                }
+                return CheckedEvalOutcome::Value(ConstValue::Int(python_mod_i64(a, b)));
+            }
+            if right.as_f64() == 0.0 {
+                return CheckedEvalOutcome::Error("modulo by zero".to_string());
+            }
+            let (a, b): (f64, f64) = (left.as_f64(), right.as_f64());
+            return CheckedEvalOutcome::Value(ConstValue::Float(a - b * (a / b).floor()));
+        },
+        "**" => apply_checked_power(left, right),
+        "<<" | ">>" => {
+            let (a, b): (Option<i64>, Option<i64>) = (left.as_i64(), right.as_i64());
+            let (a, b): (i64, i64) = match (a, b) {
+                (Some(a), Some(b)) => (a, b),
+                _ => return CheckedEvalOutcome::Error(format!("'{}' requires integer operands", op)),
+            };
+            if !(0..64).contains(&b) {
+                return CheckedEvalOutcome::Error("shift amount out of range".to_string());
+            }
+            let shift: u32 = b as u32;
+            let result: Option<i64> = if op == "<<" { a.checked_shl(shift) } else { a.checked_shr(shift) };
+            return match result {
+                Some(value) => CheckedEvalOutcome::Value(ConstValue::Int(value)),
+                None => CheckedEvalOutcome::Error("arithmetic overflow evaluating a constant expression".to_string()),
+            };
+        },
+        "&" | "|" | "^" => {
+            let (a, b): (Option<i64>, Option<i64>) = (left.as_i64(), right.as_i64());
+            let (a, b): (i64, i64) = match (a, b) {
+                (Some(a), Some(b)) => (a, b),
+                _ => return CheckedEvalOutcome::Error(format!("'{}' requires integer operands", op)),
+            };
+            let result: i64 = match op {
+                "&" => a & b,
+                "|" => a | b,
+                _ => a ^ b,
+            };
+            return CheckedEvalOutcome::Value(ConstValue::Int(result));
+        },
+        // Comparisons, `and`/`or`, `in`/`is`, and `@` aren't part of the checked-arithmetic subset
+        // this evaluates -- left unevaluated rather than treated as an error.
+        _ => CheckedEvalOutcome::NotConstant,
+    }
+}
+
+// Evaluates `expr` with checked arithmetic if it is built entirely out of int/float/bool literals,
+// the binary operators, unary `-`/`+`/`~`, and parentheses -- the same literal subset
+// `try_eval_numeric` folds, but distinguishing "not constant" (a Name, Call, Subscript, Attribute,
+// or Str anywhere in it) from "constant but the arithmetic can't actually be carried out" (overflow,
+// division/modulo by zero, a bitwise/shift operator applied to a float), which folding to a bare
+// `f64` can't represent. `True`/`False` are special-cased here since the tokenizer has no dedicated
+// boolean-literal token and otherwise parses them as a plain `Expr::Name`.
+pub fn try_eval_checked(expr: &Expr) -> CheckedEvalOutcome {
+    match expr {
+        Expr::Number(n) => {
+            if n.contains('.') {
+                return match n.parse::<f64>() {
+                    Ok(value) => CheckedEvalOutcome::Value(ConstValue::Float(value)),
+                    Err(_) => CheckedEvalOutcome::NotConstant,
+                };
+            }
+            return match n.parse::<i64>() {
+                Ok(value) => CheckedEvalOutcome::Value(ConstValue::Int(value)),
+                Err(_) => CheckedEvalOutcome::NotConstant,
+            };
+        },
+        Expr::Name(n) if n == "True" => CheckedEvalOutcome::Value(ConstValue::Bool(true)),
+        Expr::Name(n) if n == "False" => CheckedEvalOutcome::Value(ConstValue::Bool(false)),
+        Expr::UnaryOp { op, operand } => {
+            match try_eval_checked(operand) {
+                CheckedEvalOutcome::Value(value) => apply_checked_unary(op, value),
+                other => other,
+            }
+        },
+        Expr::BinaryOp { op, left, right } => {
+            let left_result: CheckedEvalOutcome = try_eval_checked(left);
+            let right_result: CheckedEvalOutcome = try_eval_checked(right);
+            // A name/call/subscript anywhere in the expression leaves the *whole* expression
+            // unevaluated, even if the other operand would itself have errored -- referencing a
+            // name isn't a mistake, so it takes priority over an arithmetic error.
+            if matches!(left_result, CheckedEvalOutcome::NotConstant) || matches!(right_result, CheckedEvalOutcome::NotConstant) {
+                return CheckedEvalOutcome::NotConstant;
+            }
+            let left_value: ConstValue = match left_result {
+                CheckedEvalOutcome::Value(value) => value,
+                CheckedEvalOutcome::Error(message) => return CheckedEvalOutcome::Error(message),
+                CheckedEvalOutcome::NotConstant => unreachable!(),
+            };
+            let right_value: ConstValue = match right_result {
+                CheckedEvalOutcome::Value(value) => value,
+                CheckedEvalOutcome::Error(message) => return CheckedEvalOutcome::Error(message),
+                CheckedEvalOutcome::NotConstant => unreachable!(),
+            };
+            apply_checked_binary(op, left_value, right_value)
+        },
+        _ => CheckedEvalOutcome::NotConstant,
+    }
+}
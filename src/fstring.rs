@@ -0,0 +1,167 @@
+// A small sub-parser for f-string interpolation. The rest of this crate treats every string
+// literal as opaque text (see cst::lex's StringLiteral/UnterminatedStringLiteral tokens), so a
+// reference like `i` in `f"{i} is divisible by 5"` is otherwise invisible to anything walking a
+// Function's or Class's source. This module finds f-string literal tokens -- a string literal
+// whose immediately preceding token ends in one of Python's `f`/`F`/`rf`/`fr` prefixes, in any
+// case or letter order -- and walks each one's body tracking brace depth to pull out the raw
+// expression text of every `{...}` replacement field. `{{` and `}}` are escaped literal braces,
+// not fields; a field's expression stops at a top-level `!` conversion or `:` format spec, but
+// not at either of those nested inside brackets (so `f"{d[1:2]}"` keeps its slice colon and
+// `f"{x:>{width}}"` keeps the nested format-spec field from being mistaken for the end of `x`).
+//
+// Like cst.rs's own lexer, this doesn't understand triple-quoted strings -- `lex` itself closes a
+// literal at the first unescaped occurrence of its quote character, so a triple-quoted f-string's
+// body isn't captured correctly yet. That's an existing limitation of `lex`, not something new
+// introduced here.
+
+use crate::cst::{lex, Token, TokenKind};
+
+fn is_fstring_prefix(prefix: &str) -> bool {
+    return matches!(prefix.to_lowercase().as_str(), "f" | "rf" | "fr");
+}
+
+// True if the `Other` token immediately preceding a string literal ends in an f-string prefix.
+// `lex` merges any run of non-whitespace/non-bracket/non-quote characters into one `Other` token,
+// so the characters right before the quote -- whatever comes earlier in that run -- are exactly
+// the string's prefix letters.
+fn ends_with_fstring_prefix(preceding: &str) -> bool {
+    let trimmed: &str = preceding.trim_end();
+    for length in [2usize, 1usize] {
+        if trimmed.len() < length {
+            continue;
+        }
+        let suffix: &str = &trimmed[trimmed.len() - length..];
+        if is_fstring_prefix(suffix) {
+            return true;
+        }
+    }
+    return false;
+}
+
+// Extracts the raw text of every `{...}` replacement field inside `body` (a string literal's text
+// with its surrounding quote characters already stripped).
+fn extract_expressions_from_body(body: &str) -> Vec<String> {
+    let chars: Vec<char> = body.chars().collect();
+    let mut expressions: Vec<String> = Vec::new();
+    let mut index: usize = 0;
+
+    while index < chars.len() {
+        match chars[index] {
+            '{' => {
+                if index + 1 < chars.len() && chars[index + 1] == '{' {
+                    index += 2;
+                    continue;
+                }
+                index += 1;
+                let expr_start: usize = index;
+                let mut expr_end: Option<usize> = None;
+                let mut depth: i32 = 0;
+                let mut in_single_quotations: bool = false;
+                let mut in_double_quotations: bool = false;
+
+                while index < chars.len() {
+                    let c: char = chars[index];
+                    if in_single_quotations {
+                        if c == '\\' && index + 1 < chars.len() { index += 2; continue; }
+                        if c == '\'' { in_single_quotations = false; }
+                        index += 1;
+                        continue;
+                    }
+                    if in_double_quotations {
+                        if c == '\\' && index + 1 < chars.len() { index += 2; continue; }
+                        if c == '\"' { in_double_quotations = false; }
+                        index += 1;
+                        continue;
+                    }
+                    match c {
+                        '\'' => { in_single_quotations = true; index += 1; },
+                        '\"' => { in_double_quotations = true; index += 1; },
+                        '(' | '[' | '{' => { depth += 1; index += 1; },
+                        ')' | ']' => { depth -= 1; index += 1; },
+                        '}' => {
+                            if depth == 0 {
+                                if expr_end.is_none() { expr_end = Some(index); }
+                                index += 1;
+                                break;
+                            }
+                            depth -= 1;
+                            index += 1;
+                        },
+                        '!' if depth == 0 && expr_end.is_none() => {
+                            let is_not_equal: bool = index + 1 < chars.len() && chars[index + 1] == '=';
+                            if !is_not_equal { expr_end = Some(index); }
+                            index += 1;
+                        },
+                        ':' if depth == 0 && expr_end.is_none() => {
+                            expr_end = Some(index);
+                            index += 1;
+                        },
+                        _ => { index += 1; },
+                    }
+                }
+
+                let end: usize = expr_end.unwrap_or(expr_start);
+                expressions.push(chars[expr_start..end].iter().collect::<String>().trim().to_string());
+            },
+            '}' => {
+                if index + 1 < chars.len() && chars[index + 1] == '}' {
+                    index += 2;
+                } else {
+                    index += 1;
+                }
+            },
+            _ => { index += 1; },
+        }
+    }
+
+    return expressions;
+}
+
+// Scans `text` for f-string literal tokens and returns the raw expression text of every `{...}`
+// replacement field across all of them, in source order.
+pub fn extract_fstring_expressions(text: &str) -> Vec<String> {
+    let tokens: Vec<Token> = lex(text);
+    let mut expressions: Vec<String> = Vec::new();
+
+    for (index, token) in tokens.iter().enumerate() {
+        if !matches!(token.kind, TokenKind::StringLiteral | TokenKind::UnterminatedStringLiteral) {
+            continue;
+        }
+        let is_fstring: bool = index > 0 && match &tokens[index - 1].kind {
+            TokenKind::Other => ends_with_fstring_prefix(&tokens[index - 1].text),
+            _ => false,
+        };
+        if !is_fstring {
+            continue;
+        }
+
+        let quote: char = match token.text.chars().next() {
+            Some(c) => c,
+            None => continue,
+        };
+        let body: &str = if token.text.len() >= 2 && token.text.ends_with(quote) {
+            &token.text[quote.len_utf8()..token.text.len() - quote.len_utf8()]
+        } else {
+            &token.text[quote.len_utf8()..]
+        };
+        expressions.extend(extract_expressions_from_body(body));
+    }
+
+    return expressions;
+}
+
+// Per-line convenience wrapper, for callers walking a `Vec<Line>` the way `Function`/`Class`
+// already do for their own source.
+pub fn extract_fstring_expressions_from_line(line: &crate::Line) -> Vec<String> {
+    return extract_fstring_expressions(line.get_text());
+}
+
+// Walks every line of `source` and collects the f-string expressions found across all of them, in
+// order.
+pub fn extract_fstring_expressions_from_lines(source: &Vec<crate::Line>) -> Vec<String> {
+    let mut expressions: Vec<String> = Vec::new();
+    for line in source {
+        expressions.extend(extract_fstring_expressions_from_line(line));
+    }
+    return expressions;
+}
@@ -0,0 +1,191 @@
+// Post-parse inheritance analysis over a File: resolves each Class's `parent` string into links
+// to the sibling Class objects actually parsed from the same file (multiple comma-separated bases
+// are all looked up), records any base that doesn't match a parsed Class as `unresolved_parents`
+// (an external/imported superclass, e.g. a stdlib or third-party base this crate never parsed),
+// and then walks every resolved ancestor to answer two questions per class: does `__init__` (if
+// the class defines one) ever call `super().__init__(...)`, given that at least one ancestor
+// itself declares instance attributes; and which `self.<attr>` attributes the class's own methods
+// read that it never assigns itself but that a resolved ancestor does.
+//
+// Instance attributes are discovered the same way for every class: each of its methods' source
+// lines is fed through the existing `Assignment::new`/`Line::is_assignment` machinery, keeping
+// only assignments whose target is exactly `self.<identifier>` (a subscript or nested-attribute
+// target like `self.data[k]` or `self.a.b` isn't a fresh attribute declaration, so both are
+// skipped).
+
+use std::collections::{HashMap, HashSet};
+
+use regex::Regex;
+
+use crate::{Class, File};
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ClassInheritanceReport {
+    pub class_name: String,
+    pub resolved_parents: Vec<String>,
+    pub unresolved_parents: Vec<String>,
+    pub missing_super_call: bool,
+    pub inherited_attributes: Vec<String>,
+    pub undeclared_attribute_uses: Vec<String>,
+}
+
+// Splits a class's raw `parent` capture (e.g. "Shape, Banana") on top-level commas -- bracket-depth
+// aware so a parameterized base like "Generic[int, str]" doesn't get split on its inner comma.
+fn split_parents(parent: &str) -> Vec<String> {
+    let mut names: Vec<String> = Vec::new();
+    let mut current: String = String::new();
+    let mut depth: i32 = 0;
+    for c in parent.chars() {
+        match c {
+            '[' | '(' => { depth += 1; current.push(c); },
+            ']' | ')' => { depth -= 1; current.push(c); },
+            ',' if depth == 0 => {
+                if !current.trim().is_empty() {
+                    names.push(current.trim().to_string());
+                }
+                current = String::new();
+            },
+            _ => current.push(c),
+        }
+    }
+    if !current.trim().is_empty() {
+        names.push(current.trim().to_string());
+    }
+    return names;
+}
+
+// Collects every Class in `file`, keyed by simple name, recursing into nested classes the same way
+// `intern::intern_class` does -- so a class can resolve a parent nested inside another class, not
+// just a file-level sibling.
+fn collect_classes_by_name<'a>(classes: &'a Vec<Class>, index: &mut HashMap<String, &'a Class>) {
+    for class in classes {
+        index.insert(class.get_name().clone(), class);
+        collect_classes_by_name(class.get_classes(), index);
+    }
+}
+
+// Instance attributes this class's own methods assign directly via `self.<name> = ...`, by name --
+// `Class::instance_attributes` already does the actual scan (including nested `def`s) and returns
+// the full `Assignment`s; this analysis only needs the names.
+fn declared_instance_attributes(class: &Class) -> Vec<String> {
+    return class.instance_attributes().iter().map(|assignment| assignment.get_name().clone()).collect();
+}
+
+// Every `self.<name>` reference anywhere in this class's own methods (both reads and assignment
+// targets -- the caller subtracts the class's own declared attributes afterwards).
+fn referenced_self_attributes(class: &Class) -> HashSet<String> {
+    let self_attr_reference: Regex = Regex::new(r"self\.([A-Za-z_]\w*)").unwrap();
+    let mut referenced: HashSet<String> = HashSet::new();
+    for method in class.get_methods() {
+        for line in method.get_source().iter().skip(1) {
+            for captures in self_attr_reference.captures_iter(line.get_text()) {
+                referenced.insert(captures[1].to_string());
+            }
+        }
+    }
+    return referenced;
+}
+
+// True if any method's source calls `super().__init__(` somewhere in its body.
+fn calls_super_init(class: &Class) -> bool {
+    for method in class.get_methods() {
+        for line in method.get_source() {
+            if line.get_text().replace(' ', "").contains("super().__init__(") {
+                return true;
+            }
+        }
+    }
+    return false;
+}
+
+fn has_init(class: &Class) -> bool {
+    return class.get_methods().iter().any(|method| method.get_name() == "__init__");
+}
+
+// Walks the resolved ancestor chain, collecting every ancestor reachable through resolved parent
+// links (recursing through each ancestor's own resolved parents in turn, guarding against cycles
+// with `visited`). Ordering doesn't affect attribute discovery -- every ancestor's own declared
+// attributes count as inherited regardless of where they sit in the chain -- so this isn't a full
+// C3-style MRO, just a linearized reachable set.
+fn linearize_ancestors<'a>(class: &'a Class, classes_by_name: &HashMap<String, &'a Class>, visited: &mut HashSet<String>) -> Vec<&'a Class> {
+    let mut ancestors: Vec<&Class> = Vec::new();
+    for parent_name in split_parents(class.get_parent()) {
+        if let Some(parent) = classes_by_name.get(&parent_name) {
+            if visited.insert(parent_name.clone()) {
+                ancestors.push(parent);
+                ancestors.extend(linearize_ancestors(parent, classes_by_name, visited));
+            }
+        }
+    }
+    return ancestors;
+}
+
+fn analyze_class<'a>(class: &'a Class, classes_by_name: &HashMap<String, &'a Class>) -> ClassInheritanceReport {
+    let mut resolved_parents: Vec<String> = Vec::new();
+    let mut unresolved_parents: Vec<String> = Vec::new();
+    for parent_name in split_parents(class.get_parent()) {
+        if parent_name == "object" {
+            continue;
+        }
+        if classes_by_name.contains_key(&parent_name) {
+            resolved_parents.push(parent_name);
+        } else {
+            unresolved_parents.push(parent_name);
+        }
+    }
+
+    let mut visited: HashSet<String> = HashSet::new();
+    let ancestors: Vec<&Class> = linearize_ancestors(class, classes_by_name, &mut visited);
+
+    let mut inherited_seen: HashSet<String> = HashSet::new();
+    let mut inherited_attributes: Vec<String> = Vec::new();
+    let mut any_ancestor_has_attributes: bool = false;
+    for ancestor in &ancestors {
+        let ancestor_attributes: Vec<String> = declared_instance_attributes(ancestor);
+        if !ancestor_attributes.is_empty() {
+            any_ancestor_has_attributes = true;
+        }
+        for attribute in ancestor_attributes {
+            if inherited_seen.insert(attribute.clone()) {
+                inherited_attributes.push(attribute);
+            }
+        }
+    }
+
+    let own_attributes: HashSet<String> = declared_instance_attributes(class).into_iter().collect();
+    let referenced: HashSet<String> = referenced_self_attributes(class);
+    let mut undeclared_attribute_uses: Vec<String> = inherited_attributes.iter()
+        .filter(|attribute| referenced.contains(*attribute) && !own_attributes.contains(*attribute))
+        .cloned()
+        .collect();
+    undeclared_attribute_uses.sort();
+
+    let missing_super_call: bool = has_init(class) && any_ancestor_has_attributes && !calls_super_init(class);
+
+    return ClassInheritanceReport {
+        class_name: class.get_name().clone(),
+        resolved_parents: resolved_parents,
+        unresolved_parents: unresolved_parents,
+        missing_super_call: missing_super_call,
+        inherited_attributes: inherited_attributes,
+        undeclared_attribute_uses: undeclared_attribute_uses,
+    };
+}
+
+fn collect_reports<'a>(classes: &'a Vec<Class>, classes_by_name: &HashMap<String, &'a Class>, reports: &mut Vec<ClassInheritanceReport>) {
+    for class in classes {
+        reports.push(analyze_class(class, classes_by_name));
+        collect_reports(class.get_classes(), classes_by_name, reports);
+    }
+}
+
+// Runs the full analysis over every class in `file` (including nested classes), returning one
+// report per class in file order.
+pub fn analyze_inheritance(file: &File) -> Vec<ClassInheritanceReport> {
+    let mut classes_by_name: HashMap<String, &Class> = HashMap::new();
+    collect_classes_by_name(file.get_classes(), &mut classes_by_name);
+
+    let mut reports: Vec<ClassInheritanceReport> = Vec::new();
+    collect_reports(file.get_classes(), &classes_by_name, &mut reports);
+    return reports;
+}
@@ -1,55 +1,295 @@
-use std::env;
 use std::io::{BufWriter, Write};
 
-use python_script_analyser::{File, get_file_lines, vec_str_to_vec_line, write_to_writer, flush_writer};
+use clap::{Parser, Subcommand};
+
+use python_script_analyser::{
+    File, Severity, get_file_lines, vec_str_to_vec_line, write_to_writer, flush_writer,
+    write_diagnostics, OutputFormat, default_lints, run_lints, apply_lint_edits,
+    DiagnosticsConfig, Diagnostics, RuleSeverity, warning_type_from_str, OutputMode,
+    run_repl, ReplOptions, DelimitedFormat, warnings_to_delimited, dataflow_to_delimited,
+};
+use std::str::FromStr;
+
+#[derive(Parser)]
+#[command(name = "python-script-analyser", about = "Static analysis tooling for Python scripts")]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Print the full indented analysis tree for one or more files.
+    Dump {
+        files: Vec<String>,
+        /// Base indentation (in spaces) to start the tree dump at.
+        #[arg(long, default_value_t = 0)]
+        indent: usize,
+        /// Output format: 'text' (default), 'json', 'json-pretty', or 'summary'.
+        #[arg(long, default_value = "text")]
+        format: String,
+    },
+    /// Parse one or more files and report only diagnostics; exits non-zero if any fires at error severity.
+    Check {
+        files: Vec<String>,
+        #[arg(long, default_value_t = 0)]
+        indent: usize,
+        /// Downgrade a rule (by its WarningType name, e.g. 'unused-definition') to a no-op.
+        #[arg(long = "allow", value_name = "RULE")]
+        allow: Vec<String>,
+        /// Set a rule to warn-level (the default for every rule).
+        #[arg(long = "warn", value_name = "RULE")]
+        warn: Vec<String>,
+        /// Promote a rule to error-level, so a finding fails the process.
+        #[arg(long = "deny", value_name = "RULE")]
+        deny: Vec<String>,
+    },
+    /// Print counts of functions, classes, global variables and imports for one or more files.
+    Summary {
+        files: Vec<String>,
+        #[arg(long, default_value_t = 0)]
+        indent: usize,
+    },
+    /// The original single-mode entry point (tree dump + diagnostics + lints in one pass),
+    /// kept around for scripts already built against it.
+    Analyze {
+        filename: String,
+        #[arg(long = "message-format", value_name = "FORMAT", default_value = "human")]
+        message_format: String,
+        #[arg(long)]
+        write: bool,
+        #[arg(long)]
+        format: bool,
+        #[arg(long = "check")]
+        check_format: bool,
+        #[arg(long = "emit", value_name = "FORMAT")]
+        emit: Option<String>,
+        #[arg(long = "allow", value_name = "RULE")]
+        allow: Vec<String>,
+        #[arg(long = "warn", value_name = "RULE")]
+        warn: Vec<String>,
+        #[arg(long = "deny", value_name = "RULE")]
+        deny: Vec<String>,
+    },
+    /// Parse a file and open an interactive query session against it (list classes, inspect a
+    /// class's methods/parent, print a function's signature and source, search by name).
+    Repl {
+        filename: String,
+        /// Path to persist command history to across sessions.
+        #[arg(long)]
+        history: Option<String>,
+    },
+}
 
 fn main() {
-    // Initialize writer.
+    let cli: Cli = Cli::parse();
     let stdout_handle = std::io::stdout();
     let mut writer: BufWriter<Box<dyn Write>> = BufWriter::new(Box::new(stdout_handle));
-    
-    // Get command line arguments.
-    let args: Vec<String> = env::args().collect();
-    
-    // Make sure there is at least one commandline argument.
-    if args.len() <= 1 {
-        println!("Usage: python-script-analyser.exe <filename>");
-        println!("Note: This program does not check for errors, use the python interpreter for that.");
-        return;
+
+    let exit_code: i32 = match cli.command {
+        Command::Dump { files, indent, format } => {
+            let mode: OutputMode = match OutputMode::from_str(&format) {
+                Ok(mode) => mode,
+                Err(error) => {
+                    eprintln!("{}, falling back to 'text'.", error);
+                    OutputMode::PlainText
+                }
+            };
+            for filename in &files {
+                if let Some(file) = load_file(&mut writer, filename) {
+                    write_to_writer(&mut writer, file.render(mode, indent).as_bytes());
+                }
+            }
+            0
+        },
+        Command::Check { files, indent, allow, warn, deny } => {
+            let _ = indent; // diagnostics aren't an indented tree, so there's nothing to indent here.
+            let config: DiagnosticsConfig = build_diagnostics_config(&allow, &warn, &deny);
+            let mut any_errors: bool = false;
+            for filename in &files {
+                if let Some(file) = load_file(&mut writer, filename) {
+                    if run_check(&mut writer, &file, &config) {
+                        any_errors = true;
+                    }
+                }
+            }
+            if any_errors { 1 } else { 0 }
+        },
+        Command::Summary { files, indent } => {
+            for filename in &files {
+                if let Some(file) = load_file(&mut writer, filename) {
+                    write_to_writer(&mut writer, render_summary(filename, &file, indent).as_bytes());
+                }
+            }
+            0
+        },
+        Command::Analyze { filename, message_format, write, format, check_format, emit, allow, warn, deny } => {
+            run_analyze(&mut writer, &filename, &message_format, write, format, check_format, emit, &allow, &warn, &deny)
+        },
+        Command::Repl { filename, history } => {
+            match load_file(&mut writer, &filename) {
+                Some(file) => {
+                    let options: ReplOptions = ReplOptions { history_path: history };
+                    let stdin = std::io::stdin();
+                    let mut input = stdin.lock();
+                    run_repl(&file, &mut input, &mut writer, &options);
+                    0
+                },
+                None => 1,
+            }
+        },
+    };
+
+    flush_writer(&mut writer);
+    if exit_code != 0 {
+        std::process::exit(exit_code);
     }
-    
-    // Assume that the first argument is the filename of the python script.
-    let filename: &str = &args[1];
-    
-    // Read file contents.
-    let lines = match get_file_lines(filename) {
-        Ok(lines) => lines, 
+}
+
+fn load_file(writer: &mut BufWriter<Box<dyn Write>>, filename: &str) -> Option<File> {
+    let lines: Vec<String> = match get_file_lines(filename) {
+        Ok(lines) => lines,
         Err(error) => {
             eprintln!("An error occured while trying to read the file {}: {:?}", filename, error);
-            return;
+            return None;
         }
     };
-    
-    // TODO: Do one pass over lines to check for indentation inconsistencies.
-    
-    
-    // Handle file.
-    let file: File = File::new(filename, &vec_str_to_vec_line(&lines), &mut writer);
-    
-    // Print file data.
-    let fas: String = file.as_string(0);
-    let fas_bytes: &[u8] = fas.as_bytes();
-    write_to_writer(&mut writer, fas_bytes);
-    
-    file.scan(&mut writer);
-    
-    let buffer_vec: Vec<u8> = writer.buffer().to_vec();
-    let buffer: String = String::from_utf8(buffer_vec).unwrap();
-    
-    // Check occurences of "WARNING".
-    let number_of_warnings: usize = buffer.matches("WARNING").count();
-    write_to_writer(&mut writer, format!("Number of warnings: {}\n", number_of_warnings).as_bytes());
-    
-    // Flush writer.
-    flush_writer(&mut writer);
+    return Some(File::new(filename, &vec_str_to_vec_line(&lines), writer));
+}
+
+fn build_diagnostics_config(allow: &Vec<String>, warn: &Vec<String>, deny: &Vec<String>) -> DiagnosticsConfig {
+    let mut config: DiagnosticsConfig = DiagnosticsConfig::new();
+    for (rules, severity) in [(allow, RuleSeverity::Allow), (warn, RuleSeverity::Warn), (deny, RuleSeverity::Error)] {
+        for rule in rules {
+            match warning_type_from_str(rule) {
+                Some(warning_type) => config.set_severity(warning_type, severity),
+                None => eprintln!("Unknown rule name '{}', ignoring.", rule),
+            }
+        }
+    }
+    return config;
+}
+
+// Reports diagnostics (both the scan()-derived ones and the configurable rule-based ones) for
+// `file` and returns true if anything at error severity fired.
+fn run_check(writer: &mut BufWriter<Box<dyn Write>>, file: &File, config: &DiagnosticsConfig) -> bool {
+    let diagnostics = file.scan_diagnostics();
+    write_diagnostics(writer, &diagnostics, OutputFormat::Text, file.get_source());
+    let has_scan_errors: bool = diagnostics.iter().any(|d| d.get_severity() == Severity::Error);
+
+    let rule_diagnostics: Diagnostics = Diagnostics::collect(file, config);
+    write_to_writer(writer, rule_diagnostics.as_text().as_bytes());
+
+    return has_scan_errors || rule_diagnostics.has_errors();
+}
+
+fn render_summary(filename: &str, file: &File, indent: usize) -> String {
+    let spaces: String = vec![' '; indent].iter().collect();
+    return format!("{}{}: {}", spaces, filename, file.render(OutputMode::Summary, indent));
+}
+
+fn run_analyze(
+    writer: &mut BufWriter<Box<dyn Write>>,
+    filename: &str,
+    message_format: &str,
+    write_fixes: bool,
+    reformat: bool,
+    check_format: bool,
+    emit: Option<String>,
+    allow: &Vec<String>,
+    warn: &Vec<String>,
+    deny: &Vec<String>,
+) -> i32 {
+    let output_format: OutputFormat = match message_format {
+        "json" => OutputFormat::Json,
+        "human" => OutputFormat::Text,
+        other => {
+            eprintln!("Unknown --message-format value '{}', falling back to 'human'.", other);
+            OutputFormat::Text
+        }
+    };
+
+    let lines: Vec<String> = match get_file_lines(filename) {
+        Ok(lines) => lines,
+        Err(error) => {
+            eprintln!("An error occured while trying to read the file {}: {:?}", filename, error);
+            return 1;
+        }
+    };
+
+    let file: File = File::new(filename, &vec_str_to_vec_line(&lines), writer);
+
+    if let Some(emit_format) = emit.as_deref() {
+        if emit_format == "json" {
+            write_to_writer(writer, file.to_json().as_bytes());
+            return 0;
+        }
+        if emit_format == "csv" {
+            file.write_csv(writer);
+            return 0;
+        }
+        if emit_format == "tree-csv" || emit_format == "tree-tsv" {
+            let format: DelimitedFormat = if emit_format == "tree-csv" { DelimitedFormat::csv() } else { DelimitedFormat::tsv() };
+            file.write_csv_with_format(writer, &format);
+            return 0;
+        }
+        if emit_format == "warnings-csv" || emit_format == "warnings-tsv" {
+            let format: DelimitedFormat = if emit_format == "warnings-csv" { DelimitedFormat::csv() } else { DelimitedFormat::tsv() };
+            write_to_writer(writer, warnings_to_delimited(&file.scan_warnings(), &format).as_bytes());
+            return 0;
+        }
+        if emit_format == "dataflow-csv" || emit_format == "dataflow-tsv" {
+            let format: DelimitedFormat = if emit_format == "dataflow-csv" { DelimitedFormat::csv() } else { DelimitedFormat::tsv() };
+            write_to_writer(writer, dataflow_to_delimited(&file.analyze_dataflow(), &format).as_bytes());
+            return 0;
+        }
+        eprintln!("Unknown --emit value '{}', ignoring.", emit_format);
+    }
+
+    let fas: String = match output_format {
+        OutputFormat::Json => file.to_json(),
+        OutputFormat::Text => file.as_string(0),
+    };
+    write_to_writer(writer, fas.as_bytes());
+
+    let diagnostics = file.scan_diagnostics();
+    write_diagnostics(writer, &diagnostics, output_format, file.get_source());
+    write_to_writer(writer, format!("Number of warnings: {}\n", diagnostics.len()).as_bytes());
+
+    let lints = default_lints();
+    let lint_findings = run_lints(&file, &lints);
+    for finding in &lint_findings {
+        write_to_writer(writer, finding.as_text().as_bytes());
+    }
+
+    let diagnostics_config: DiagnosticsConfig = build_diagnostics_config(allow, warn, deny);
+    let rule_diagnostics: Diagnostics = Diagnostics::collect(&file, &diagnostics_config);
+    write_to_writer(writer, rule_diagnostics.as_text().as_bytes());
+    let has_rule_errors: bool = rule_diagnostics.has_errors();
+
+    if write_fixes {
+        let mut fixed_lines: Vec<String> = lines.clone();
+        apply_lint_edits(&mut fixed_lines, &lint_findings);
+        match std::fs::write(filename, fixed_lines.join("\n") + "\n") {
+            Ok(()) => write_to_writer(writer, b"Applied lint fixes to the source file.\n"),
+            Err(error) => eprintln!("An error occured while writing fixes to {}: {:?}", filename, error),
+        }
+    }
+
+    if check_format {
+        let diff: String = file.format_diff();
+        if diff.is_empty() {
+            write_to_writer(writer, b"File is already formatted.\n");
+        } else {
+            write_to_writer(writer, diff.as_bytes());
+        }
+    } else if reformat {
+        let formatted_lines: Vec<String> = file.format_lines();
+        match std::fs::write(filename, formatted_lines.join("\n") + "\n") {
+            Ok(()) => write_to_writer(writer, b"Reformatted the source file.\n"),
+            Err(error) => eprintln!("An error occured while reformatting {}: {:?}", filename, error),
+        }
+    }
+
+    return if has_rule_errors { 1 } else { 0 };
 }
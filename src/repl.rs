@@ -0,0 +1,184 @@
+// An interactive query REPL over an already-parsed File, for exploring a large file (something
+// `mypy_gclogger`-sized) without re-running a one-shot `dump`/`summary` subcommand per question.
+//
+// True interactive tab-completion (binding the Tab key mid-line during raw-terminal input) needs
+// a line-editing crate such as `rustyline` -- this tree has no Cargo.toml to declare that
+// dependency against, so this REPL reads plain lines from stdin instead and exposes completion as
+// an explicit `complete <prefix>` command listing every discovered identifier (class, method,
+// function and global variable name, gathered via `File::walk()`) starting with that prefix.
+// Persistent history needs only `std::fs`: each accepted command is appended to a history file as
+// it's entered, and the file's prior contents are loaded back into the in-memory history at
+// startup, so `history` shows commands from earlier sessions too.
+//
+// Output is written through the same `BufWriter<Box<dyn Write>>` already threaded through
+// `Class::new`/`Function::new`, so REPL output formatting matches the rest of the CLI.
+
+use std::io::{BufRead, BufWriter, Write};
+
+use crate::{write_to_writer, Class, File, Function};
+
+pub struct ReplOptions {
+    pub history_path: Option<String>,
+}
+
+// Every class/method/function/global-variable name discovered in `file`, via the same walk
+// `File::walk()` already exposes -- used both for `complete`/`search` and loaded once up front so
+// repeated commands don't re-walk the tree.
+fn collect_identifiers(file: &File) -> Vec<String> {
+    let mut identifiers: Vec<String> = Vec::new();
+    for variable in file.get_global_variables() {
+        identifiers.push(variable.get_name().clone());
+    }
+    for entry in file.walk() {
+        identifiers.push(entry.path.clone());
+    }
+    identifiers.sort();
+    identifiers.dedup();
+    return identifiers;
+}
+
+fn find_class<'a>(file: &'a File, name: &str) -> Option<&'a Class> {
+    for entry in file.walk().classes() {
+        if let crate::Node::Class(class) = entry.node {
+            if class.get_name() == name {
+                return Some(class);
+            }
+        }
+    }
+    return None;
+}
+
+fn find_function<'a>(file: &'a File, name: &str) -> Option<&'a Function> {
+    for entry in file.walk().functions() {
+        if let crate::Node::Function(function) = entry.node {
+            if function.get_name() == name {
+                return Some(function);
+            }
+        }
+    }
+    return None;
+}
+
+fn print_help(writer: &mut BufWriter<Box<dyn Write>>) {
+    write_to_writer(writer, b"Commands:\n");
+    write_to_writer(writer, b"  classes               list every class (with its resolved parent)\n");
+    write_to_writer(writer, b"  class <Name>          show a class's parent and its methods\n");
+    write_to_writer(writer, b"  function <Name>       show a function's signature and source\n");
+    write_to_writer(writer, b"  search <term>         list identifiers containing <term>\n");
+    write_to_writer(writer, b"  complete <prefix>     list identifiers starting with <prefix>\n");
+    write_to_writer(writer, b"  history               show commands entered this session (and earlier, if persisted)\n");
+    write_to_writer(writer, b"  help                  show this message\n");
+    write_to_writer(writer, b"  exit | quit           leave the REPL\n");
+}
+
+fn run_command(file: &File, command: &str, identifiers: &Vec<String>, history: &Vec<String>, writer: &mut BufWriter<Box<dyn Write>>) {
+    let mut parts = command.splitn(2, char::is_whitespace);
+    let verb: &str = parts.next().unwrap_or("").trim();
+    let argument: &str = parts.next().unwrap_or("").trim();
+
+    match verb {
+        "help" => print_help(writer),
+        "history" => {
+            for (index, entry) in history.iter().enumerate() {
+                write_to_writer(writer, format!("{}: {}\n", index + 1, entry).as_bytes());
+            }
+        },
+        "classes" => {
+            for entry in file.walk().classes() {
+                if let crate::Node::Class(class) = entry.node {
+                    write_to_writer(writer, format!("{} (parent: {})\n", entry.path, class.get_parent()).as_bytes());
+                }
+            }
+        },
+        "class" => {
+            match find_class(file, argument) {
+                Some(class) => {
+                    write_to_writer(writer, format!("class {}({}):\n", class.get_name(), class.get_parent()).as_bytes());
+                    for method in class.get_methods() {
+                        write_to_writer(writer, format!("  def {}({})\n", method.get_name(), method.get_parameters().join(", ")).as_bytes());
+                    }
+                },
+                None => write_to_writer(writer, format!("No class named '{}'.\n", argument).as_bytes()),
+            }
+        },
+        "function" | "func" => {
+            match find_function(file, argument) {
+                Some(function) => {
+                    let return_type: String = match function.get_return_type() {
+                        Some(return_type) => format!(" -> {}", return_type),
+                        None => "".to_string(),
+                    };
+                    write_to_writer(writer, format!("def {}({}){}:\n", function.get_name(), function.get_parameters().join(", "), return_type).as_bytes());
+                    for line in function.get_source() {
+                        write_to_writer(writer, format!("{}\n", line.get_text()).as_bytes());
+                    }
+                },
+                None => write_to_writer(writer, format!("No function named '{}'.\n", argument).as_bytes()),
+            }
+        },
+        "search" => {
+            for identifier in identifiers {
+                if identifier.contains(argument) {
+                    write_to_writer(writer, format!("{}\n", identifier).as_bytes());
+                }
+            }
+        },
+        "complete" => {
+            for identifier in identifiers {
+                if identifier.starts_with(argument) {
+                    write_to_writer(writer, format!("{}\n", identifier).as_bytes());
+                }
+            }
+        },
+        _ => write_to_writer(writer, format!("Unknown command '{}'. Type 'help' for a list of commands.\n", verb).as_bytes()),
+    }
+}
+
+fn append_to_history_file(path: &str, command: &str) {
+    use std::fs::OpenOptions;
+    if let Ok(mut handle) = OpenOptions::new().create(true).append(true).open(path) {
+        let _ = writeln!(handle, "{}", command);
+    }
+}
+
+// Runs the REPL loop against `file`, reading commands from `input` one line at a time until EOF or
+// an `exit`/`quit` command, writing all prompts and command output through `writer`.
+pub fn run_repl(file: &File, input: &mut dyn BufRead, writer: &mut BufWriter<Box<dyn Write>>, options: &ReplOptions) {
+    let mut history: Vec<String> = Vec::new();
+    if let Some(path) = &options.history_path {
+        if let Ok(contents) = std::fs::read_to_string(path) {
+            history.extend(contents.lines().map(|line| line.to_string()));
+        }
+    }
+
+    let identifiers: Vec<String> = collect_identifiers(file);
+
+    loop {
+        write_to_writer(writer, b"> ");
+        crate::flush_writer(writer);
+
+        let mut line: String = String::new();
+        let bytes_read: usize = match input.read_line(&mut line) {
+            Ok(bytes_read) => bytes_read,
+            Err(_) => break,
+        };
+        if bytes_read == 0 {
+            break;
+        }
+
+        let command: &str = line.trim();
+        if command.is_empty() {
+            continue;
+        }
+        if command == "exit" || command == "quit" {
+            break;
+        }
+
+        history.push(command.to_string());
+        if let Some(path) = &options.history_path {
+            append_to_history_file(path, command);
+        }
+
+        run_command(file, command, &identifiers, &history, writer);
+    }
+}
@@ -0,0 +1,74 @@
+// A minimal value-plus-location pairing, modeled on the same idea as EDN's `ValueAndSpan` and
+// nac3's `Location`: instead of widening every parsed node with its own start/end bookkeeping,
+// keep the coordinates in one small `Span` and hand them out alongside the value via `Spanned<T>`.
+//
+// This only covers the line-granularity spans that fall out of data this crate already collects:
+// `Function` and `Assignment` both keep their matched `Line`(s), and `Line` already carries its
+// 1-based number, so `.span()` on each is computed on demand rather than as a new struct field --
+// the same reasoning `impl Serialize for Function` documents for `parameters_structured`: plenty
+// of test fixtures construct `Function { .. }` / `Assignment { .. }` literals against their
+// current field sets, so widening those structs would break them for a feature most callers don't
+// need. `Class`, by contrast, never retains its own `class ...:` definition line once parsed (only
+// its variables/methods/nested classes are kept), so a `Class::span()` would need a genuinely new
+// field -- left as follow-up work rather than bolted on here, since `Class { .. }` literals are
+// just as widespread in this crate's tests as `Function`'s. Sub-line byte columns for call
+// expressions and for/while loops, which this crate currently only sees as transient regex
+// `Match`es inside `scan()` rather than as stored nodes, aren't threaded yet either; `Span::from_match`
+// below is the piece a future pass would reach for once those matches get a home to live in.
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Span {
+    pub start_line: usize,
+    pub start_col: usize,
+    pub end_line: usize,
+    pub end_col: usize,
+    pub byte_offset: usize,
+}
+
+impl Span {
+
+    // A span covering an entire line, column 0 through the line's byte length.
+    pub fn for_whole_line(line_number: usize, line_text: &str, byte_offset: usize) -> Self {
+        return Span {
+            start_line: line_number,
+            start_col: 0,
+            end_line: line_number,
+            end_col: line_text.len(),
+            byte_offset: byte_offset,
+        };
+    }
+
+    // A span for a single-line regex match, using `Match::start`/`Match::end` as the columns.
+    pub fn from_match(line_number: usize, byte_offset: usize, match_start: usize, match_end: usize) -> Self {
+        return Span {
+            start_line: line_number,
+            start_col: match_start,
+            end_line: line_number,
+            end_col: match_end,
+            byte_offset: byte_offset + match_start,
+        };
+    }
+
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Spanned<T> {
+    pub value: T,
+    pub span: Span,
+}
+
+impl<T> Spanned<T> {
+
+    pub fn new(value: T, span: Span) -> Self {
+        return Spanned { value: value, span: span };
+    }
+
+    pub fn get_value(&self) -> &T {
+        return &self.value;
+    }
+
+    pub fn span(&self) -> &Span {
+        return &self.span;
+    }
+
+}
@@ -0,0 +1,237 @@
+// Structural search/replace (SSR) queries: a query/template mini-language built on top of the
+// same "name(arguments)" shape that PATTERN_FUNCTION_CALL_EXPRESSION recognizes elsewhere in this
+// crate, e.g. `foo($a, $b) ==>> bar($b, $a)`. Lives alongside the regex-classification pipeline
+// (like peg_grammar/expr_ast/cst) rather than replacing any of it, and is deliberately
+// self-contained the same way those modules are: it re-derives the small amount of bracket/quote
+// bookkeeping it needs instead of reaching into lib.rs's private helpers.
+
+use std::collections::HashMap;
+use regex::Regex;
+
+use crate::cst::{lex, TokenKind};
+use crate::Line;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct SsrQuery {
+    pattern: String,
+    template: String,
+    vars: Vec<String>,
+}
+
+impl SsrQuery {
+
+    pub fn get_pattern(&self) -> &String {
+        return &self.pattern;
+    }
+
+    pub fn get_template(&self) -> &String {
+        return &self.template;
+    }
+
+    pub fn get_vars(&self) -> &Vec<String> {
+        return &self.vars;
+    }
+
+}
+
+// Parses a query of the form `<pattern> ==>> <template>` into an SsrQuery, collecting every
+// `$name` token appearing in the pattern (in first-seen order) as a metavariable name. Returns
+// None if the query doesn't contain the `==>>` separator or either side is empty.
+pub fn parse_ssr_query(query: &str) -> Option<SsrQuery> {
+    let mut halves = query.splitn(2, "==>>");
+    let pattern: String = halves.next()?.trim().to_string();
+    let template: String = halves.next()?.trim().to_string();
+    if pattern.is_empty() || template.is_empty() {
+        return None;
+    }
+
+    let re_metavar = Regex::new(r"\$(\w+)").unwrap();
+    let mut vars: Vec<String> = Vec::new();
+    for capt in re_metavar.captures_iter(&pattern) {
+        let name: String = capt[1].to_string();
+        if !vars.contains(&name) {
+            vars.push(name);
+        }
+    }
+
+    return Some(SsrQuery { pattern: pattern, template: template, vars: vars });
+}
+
+// Splits a function-call argument list on top-level commas, respecting nested parens/brackets/
+// braces and both quote styles (escape-aware). An empty (whitespace-only) argument list splits to
+// zero arguments rather than one empty one, so `foo()` matches a zero-argument pattern.
+fn split_arguments(text: &str) -> Vec<String> {
+    let mut parts: Vec<String> = Vec::new();
+    let mut current: String = String::new();
+    let mut in_single: bool = false;
+    let mut in_double: bool = false;
+    let mut depth: i32 = 0;
+
+    let chars: Vec<char> = text.chars().collect();
+    for (index, c) in chars.iter().enumerate() {
+        let c: char = *c;
+        match c {
+            '\'' if !in_double => {
+                let escaped: bool = index > 0 && chars[index - 1] == '\\';
+                if !escaped { in_single = !in_single; }
+                current.push(c);
+            },
+            '\"' if !in_single => {
+                let escaped: bool = index > 0 && chars[index - 1] == '\\';
+                if !escaped { in_double = !in_double; }
+                current.push(c);
+            },
+            '(' | '[' | '{' if !in_single && !in_double => { depth += 1; current.push(c); },
+            ')' | ']' | '}' if !in_single && !in_double => { depth -= 1; current.push(c); },
+            ',' if !in_single && !in_double && depth == 0 => {
+                parts.push(current.trim().to_string());
+                current = String::new();
+            },
+            _ => current.push(c),
+        }
+    }
+    let last: String = current.trim().to_string();
+    if !(parts.is_empty() && last.is_empty()) {
+        parts.push(last);
+    }
+    return parts;
+}
+
+// Splits `name(arguments)` into (name, arguments); returns None if `text` isn't of that shape.
+fn split_call(text: &str) -> Option<(String, String)> {
+    let re_call = Regex::new(r"^(?P<name>[a-zA-Z_]\w*)\((?P<arguments>.*)\)$").unwrap();
+    let capt = re_call.captures(text.trim())?;
+    return Some((capt["name"].to_string(), capt["arguments"].to_string()));
+}
+
+// Matches `text` (a concrete `name(arguments)` call) against `query.pattern`, binding every
+// `$name` metavariable to the argument or sub-expression it stood for. Fails if the literal call
+// name or argument count differs, if a non-metavariable argument isn't an exact textual match, or
+// if a metavariable appears twice in the pattern but is bound to two different pieces of text.
+pub(crate) fn match_call(query: &SsrQuery, text: &str) -> Option<HashMap<String, String>> {
+    let (pattern_name, pattern_args_text) = split_call(&query.pattern)?;
+    let (call_name, call_args_text) = split_call(text)?;
+    if pattern_name != call_name {
+        return None;
+    }
+
+    let pattern_args: Vec<String> = split_arguments(&pattern_args_text);
+    let call_args: Vec<String> = split_arguments(&call_args_text);
+    if pattern_args.len() != call_args.len() {
+        return None;
+    }
+
+    let mut bindings: HashMap<String, String> = HashMap::new();
+    for (pattern_arg, call_arg) in pattern_args.iter().zip(call_args.iter()) {
+        match pattern_arg.strip_prefix('$') {
+            Some(name) => {
+                match bindings.get(name) {
+                    Some(existing) if existing != call_arg => return None,
+                    _ => { bindings.insert(name.to_string(), call_arg.clone()); },
+                }
+            },
+            None => {
+                if pattern_arg != call_arg {
+                    return None;
+                }
+            },
+        }
+    }
+    return Some(bindings);
+}
+
+// Substitutes every bound `$name` metavariable into `template`; a `$name` with no binding (a typo,
+// or a var that never occurs in the pattern) is left as-is.
+fn substitute_template(template: &str, bindings: &HashMap<String, String>) -> String {
+    let re_metavar = Regex::new(r"\$(\w+)").unwrap();
+    return re_metavar.replace_all(template, |capt: &regex::Captures| {
+        match bindings.get(&capt[1]) {
+            Some(value) => value.clone(),
+            None => capt[0].to_string(),
+        }
+    }).to_string();
+}
+
+// Finds the index of the `)` that closes the `(` at `open_index`, respecting nested brackets and
+// quotes; returns None if it's never closed within `chars`.
+fn find_matching_close_paren(chars: &Vec<char>, open_index: usize) -> Option<usize> {
+    let mut in_single: bool = false;
+    let mut in_double: bool = false;
+    let mut depth: i32 = 0;
+    let mut index: usize = open_index;
+    while index < chars.len() {
+        let c: char = chars[index];
+        match c {
+            '\'' if !in_double => {
+                let escaped: bool = index > 0 && chars[index - 1] == '\\';
+                if !escaped { in_single = !in_single; }
+            },
+            '\"' if !in_single => {
+                let escaped: bool = index > 0 && chars[index - 1] == '\\';
+                if !escaped { in_double = !in_double; }
+            },
+            '(' if !in_single && !in_double => depth += 1,
+            ')' if !in_single && !in_double => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(index);
+                }
+            },
+            _ => (),
+        }
+        index += 1;
+    }
+    return None;
+}
+
+// True if the byte offset `index` (into `line_text`) falls inside a string-literal token per the
+// cst lexer -- used to keep SSR from rewriting text that only looks like a call because it's sat
+// inside a quoted string.
+fn index_in_string_literal(line_text: &str, byte_index: usize) -> bool {
+    for token in lex(line_text) {
+        let is_string: bool = matches!(token.kind, TokenKind::StringLiteral | TokenKind::UnterminatedStringLiteral);
+        if is_string && byte_index >= token.start && byte_index < token.end {
+            return true;
+        }
+    }
+    return false;
+}
+
+// Scans `line_text` for every top-level `name(...)` call that matches `query.pattern`, and returns
+// the (old_text, new_text) rewrite for each match, left to right. A candidate whose name starts
+// inside a string literal is skipped, since it isn't a real call there.
+pub(crate) fn rewrite_line(query: &SsrQuery, line_text: &str) -> Vec<(String, String)> {
+    let mut edits: Vec<(String, String)> = Vec::new();
+    let chars: Vec<char> = line_text.chars().collect();
+    let re_call_start = Regex::new(r"[a-zA-Z_]\w*\(").unwrap();
+
+    for m in re_call_start.find_iter(line_text) {
+        if index_in_string_literal(line_text, m.start()) {
+            continue;
+        }
+        let start_char: usize = line_text[..m.start()].chars().count();
+        let open_char: usize = line_text[..m.end()].chars().count() - 1;
+        let close_char: usize = match find_matching_close_paren(&chars, open_char) {
+            Some(i) => i,
+            None => continue,
+        };
+        let call_text: String = chars[start_char..=close_char].iter().collect();
+        if let Some(bindings) = match_call(query, &call_text) {
+            let new_text: String = substitute_template(&query.template, &bindings);
+            edits.push((call_text, new_text));
+        }
+    }
+    return edits;
+}
+
+// Runs an SSR query over a file's source lines, returning one `(line_number, old_text, new_text)`
+// edit per matched call, in source order. `line_number` matches `Line::get_number`.
+pub fn run_ssr(query: &SsrQuery, source: &Vec<Line>) -> Vec<(usize, String, String)> {
+    let mut edits: Vec<(usize, String, String)> = Vec::new();
+    for line in source.iter() {
+        for (old_text, new_text) in rewrite_line(query, line.get_text()) {
+            edits.push((line.get_number(), old_text, new_text));
+        }
+    }
+    return edits;
+}
@@ -0,0 +1,117 @@
+// A recursive visitor/iterator over a parsed tree, so consumers don't have to hand-write the
+// functions/classes/nested-functions/nested-classes recursion that `File`/`Class` already need
+// internally (see `count_methods_recursive`, `intern::intern_class`, `inheritance::collect_reports`
+// for examples of that recursion being written out by hand each time it's needed).
+//
+// `File::walk()`/`Class::walk()` return a `Walk`, an iterator yielding one `WalkEntry` per
+// function/class/assignment anywhere in the tree (at any nesting depth), each carrying a
+// fully-qualified dotted path (e.g. `UpperClass.MiddleClass.LowerClass.pear`) built from the names
+// of every class/function it's nested inside, and its nesting depth (0 for something directly on
+// the File or the root Class passed to `Class::walk()`). Entries are collected eagerly into a
+// `Vec` up front rather than computed lazily -- consistent with how this crate's other "derive a
+// view over an existing tree" methods (`fstring_expressions`, `File::summarize`) already work --
+// and then wrapped in an iterator. `.functions()`/`.classes()`/`.assignments()` are filtering
+// convenience methods for the common case of wanting only one node kind; anything else
+// (`.filter(...)`, `.map(...)`, ...) works too since `Walk` is a plain `Iterator`.
+
+use crate::{Assignment, Class, Function};
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Node<'a> {
+    Function(&'a Function),
+    Class(&'a Class),
+    Assignment(&'a Assignment),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct WalkEntry<'a> {
+    pub node: Node<'a>,
+    pub path: String,
+    pub depth: usize,
+}
+
+fn joined_path(prefix: &str, name: &str) -> String {
+    if prefix.is_empty() {
+        return name.to_string();
+    }
+    return format!("{}.{}", prefix, name);
+}
+
+fn walk_function<'a>(function: &'a Function, prefix: &str, depth: usize, entries: &mut Vec<WalkEntry<'a>>) {
+    let path: String = joined_path(prefix, function.get_name());
+    entries.push(WalkEntry { node: Node::Function(function), path: path.clone(), depth: depth });
+    for nested in function.get_functions() {
+        walk_function(nested, &path, depth + 1, entries);
+    }
+}
+
+fn walk_class<'a>(class: &'a Class, prefix: &str, depth: usize, entries: &mut Vec<WalkEntry<'a>>) {
+    let path: String = joined_path(prefix, class.get_name());
+    entries.push(WalkEntry { node: Node::Class(class), path: path.clone(), depth: depth });
+    for variable in class.get_variables() {
+        let variable_path: String = joined_path(&path, variable.get_name());
+        entries.push(WalkEntry { node: Node::Assignment(variable), path: variable_path, depth: depth + 1 });
+    }
+    for method in class.get_methods() {
+        walk_function(method, &path, depth + 1, entries);
+    }
+    for nested in class.get_classes() {
+        walk_class(nested, &path, depth + 1, entries);
+    }
+}
+
+// An iterator over every function/class/assignment reachable from wherever `walk()` was called,
+// each paired with its fully-qualified dotted path and nesting depth.
+pub struct Walk<'a> {
+    entries: std::vec::IntoIter<WalkEntry<'a>>,
+}
+
+impl<'a> Walk<'a> {
+
+    pub(crate) fn from_file(file: &'a crate::File) -> Self {
+        let mut entries: Vec<WalkEntry<'a>> = Vec::new();
+        for variable in file.get_global_variables() {
+            entries.push(WalkEntry { node: Node::Assignment(variable), path: variable.get_name().clone(), depth: 0 });
+        }
+        for function in file.get_functions() {
+            walk_function(function, "", 0, &mut entries);
+        }
+        for class in file.get_classes() {
+            walk_class(class, "", 0, &mut entries);
+        }
+        return Walk { entries: entries.into_iter() };
+    }
+
+    pub(crate) fn from_class(class: &'a Class) -> Self {
+        let mut entries: Vec<WalkEntry<'a>> = Vec::new();
+        walk_class(class, "", 0, &mut entries);
+        return Walk { entries: entries.into_iter() };
+    }
+
+    // Only the entries whose node is a `Function`.
+    pub fn functions(self) -> impl Iterator<Item = WalkEntry<'a>> {
+        return self.filter(|entry| matches!(entry.node, Node::Function(_)));
+    }
+
+    // Only the entries whose node is a `Class`.
+    pub fn classes(self) -> impl Iterator<Item = WalkEntry<'a>> {
+        return self.filter(|entry| matches!(entry.node, Node::Class(_)));
+    }
+
+    // Only the entries whose node is an `Assignment` (global variables, class variables, and
+    // instance attributes discovered via `Class::instance_attributes` are NOT included here --
+    // this walks the tree `File`/`Class` already store, and instance attributes aren't stored,
+    // only computed on demand).
+    pub fn assignments(self) -> impl Iterator<Item = WalkEntry<'a>> {
+        return self.filter(|entry| matches!(entry.node, Node::Assignment(_)));
+    }
+
+}
+
+impl<'a> Iterator for Walk<'a> {
+    type Item = WalkEntry<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        return self.entries.next();
+    }
+}